@@ -0,0 +1,66 @@
+//! Compare the eager [DecodedEntry] path with the borrowed [EntryBatch] path.
+//!
+//! Run with `cargo bench --bench decode`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ctlog::ct::v1::{borrowed::EntryBatch, DecodedEntry, Entry, GetEntriesResponse};
+
+/// Build a batch the size of a typical get-entries response from the fixture entries.
+fn entries(size: usize) -> Vec<Entry> {
+    let response: GetEntriesResponse =
+        serde_json::from_str(include_str!("data/entries.json")).unwrap();
+    response.entries.into_iter().cycle().take(size).collect()
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+
+    for size in [32, 256, 1024] {
+        let entries = entries(size);
+
+        group.bench_with_input(
+            BenchmarkId::new("DecodedEntry", size),
+            &entries,
+            |b, entries| {
+                b.iter(|| {
+                    entries
+                        .iter()
+                        .map(|entry| DecodedEntry::try_from(entry).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("EntryBatch", size),
+            &entries,
+            |b, entries| {
+                b.iter(|| {
+                    let batch = EntryBatch::decode(entries).unwrap();
+                    for entry in batch.iter() {
+                        black_box(entry.unwrap());
+                    }
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("EntryBatch (parse certificates)", size),
+            &entries,
+            |b, entries| {
+                b.iter(|| {
+                    let batch = EntryBatch::decode(entries).unwrap();
+                    for entry in batch.iter() {
+                        let entry = entry.unwrap();
+                        black_box(entry.certificate().unwrap());
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
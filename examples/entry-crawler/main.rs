@@ -1,14 +1,14 @@
 use std::io::Write;
 use std::path::PathBuf;
+use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::{Parser, Subcommand};
-use oid_registry::{format_oid, OidRegistry};
-use serde_cert::Cert;
+use ctlog::ct::v1::extensions::DecodedExtension;
+use ctlog::ct::v1::summary::{EntrySummary, ENTRY_SUMMARY_VERSION};
+use ctlog::ct::v1::DecodedEntry;
+use oid_registry::Oid;
 use tokio::time::{sleep, Duration};
-use x509_parser::prelude::*;
-
-mod serde_cert;
 
 #[derive(Parser)]
 #[command(name = "entry-crawler")]
@@ -30,7 +30,7 @@ enum Commands {
         output: PathBuf,
     },
 
-    /// Convert the entries
+    /// Decode the extensions of entries saved by an older version, and update their schema version
     Convert {
         /// The input file
         #[clap(short, long, default_value = "entries.json")]
@@ -72,7 +72,7 @@ async fn crawl(url: String, output: PathBuf) -> Result<()> {
     // Get the entries, 200 at a time
     for i in (0..tree_size).step_by(200) {
         // Get the entries
-        let entries = ctlog.get_entries_decoded(i, i + 199).await?;
+        let entries = ctlog.get_entries(i, i + 199).await?.entries;
         println!(
             "Getting entries {} to {}, len {}",
             i,
@@ -80,13 +80,19 @@ async fn crawl(url: String, output: PathBuf) -> Result<()> {
             entries.len()
         );
 
-        // Write the entries to the file
+        // Write the entries to the file, skipping those that do not decode
         entries
             .iter()
             .enumerate()
             .try_for_each(|(ii, e)| -> Result<()> {
-                let mut e: Cert = e.into();
-                e.index = i + ii as u64;
+                let index = i + ii as u64;
+                let e = match DecodedEntry::try_from(e) {
+                    Ok(e) => EntrySummary::from(&e).with_index(index),
+                    Err(err) => {
+                        println!("Skipping entry {}: {}", index, err);
+                        return Ok(());
+                    }
+                };
                 serde_json::to_writer_pretty(&out, &e)?;
                 writeln!(out, ",")?;
 
@@ -105,87 +111,27 @@ async fn crawl(url: String, output: PathBuf) -> Result<()> {
 async fn convert(input: PathBuf, output: PathBuf) -> Result<()> {
     let input = std::fs::File::open(input)?;
 
-    let mut entries: Vec<Cert> = serde_json::from_reader(input)?;
-
-    // Example of parsing the AuthorityInfoAccess and CrlDistributionPoint extension
+    let mut entries: Vec<EntrySummary> = serde_json::from_reader(input)?;
 
+    // Entries saved by older versions have the raw extension values only. Version 1 entries
+    // are valid version 2 entries.
     println!("Converting {} entries", entries.len());
 
-    let mut idx = 0;
-    let total = entries.len();
-
     for entry in &mut entries {
-        println!("Converting entry {idx}/{total}");
-        idx += 1;
-        for ext in &mut entry.raw_extensions {
-            let flag = if ext.oid
-                == format_oid(
-                    &oid_registry::OID_PKIX_AUTHORITY_INFO_ACCESS,
-                    &OidRegistry::default().with_x509(),
-                ) {
-                0
-            } else if ext.oid
-                == format_oid(
-                    &oid_registry::OID_X509_EXT_CRL_DISTRIBUTION_POINTS,
-                    &OidRegistry::default().with_x509(),
-                )
-            {
-                1
-            } else {
-                continue;
-            };
-
-            let value: Vec<u8> = ext
-                .value
-                .split_terminator(":")
-                .map(|s| u8::from_str_radix(s, 16).unwrap())
-                .collect();
-
-            match flag {
-                0 => {
-                    let (_, access) = extensions::AuthorityInfoAccess::from_der(&value).unwrap();
-                    let format_access: Vec<String> = access
-                        .accessdescs
-                        .iter()
-                        .map(|a| {
-                            let method =
-                                format_oid(&a.access_method, &OidRegistry::default().with_x509());
-                            let location = a.access_location.to_string();
-                            format!("{}: {}", method, location)
-                        })
-                        .collect();
-
-                    ext.value = format_access.join(",").to_string();
-                }
-                1 => {
-                    let (_, points) = extensions::CRLDistributionPoints::from_der(&value).unwrap();
-                    let format_points: Vec<String> = points
-                        .points
-                        .iter()
-                        .map(|p| {
-                            let name = &p
-                                .distribution_point
-                                .clone()
-                                .map(|n| format!("{n:?}"))
-                                .unwrap_or("".to_string());
-                            let reasons = &p
-                                .reasons
-                                .clone()
-                                .map(|r| format!("{r}"))
-                                .unwrap_or("".to_string());
-                            let issuer = &p
-                                .crl_issuer
-                                .clone()
-                                .map(|i| format!("{i:?}"))
-                                .unwrap_or("".to_string());
-                            format!("{}:{}:{}", name, reasons, issuer)
-                        })
-                        .collect();
-
-                    ext.value = format_points.join(";").to_string();
-                }
-                _ => unreachable!(),
-            }
+        entry.schema_version = ENTRY_SUMMARY_VERSION;
+        for ext in entry
+            .certificate
+            .iter_mut()
+            .flat_map(|certificate| certificate.extensions.iter_mut())
+            .filter(|ext| ext.decoded.is_none())
+        {
+            let oid =
+                Oid::from_str(&ext.oid).map_err(|e| anyhow!("invalid OID {}: {e:?}", ext.oid))?;
+            let value: Vec<u8> = (0..ext.value.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&ext.value[i..i + 2], 16))
+                .collect::<Result<_, _>>()?;
+            ext.decoded = DecodedExtension::from_value(&oid, &value);
         }
     }
 
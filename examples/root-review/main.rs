@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use ctlog::truststore::TrustStore;
+use ctlog::CTLogV1;
+
+/// Compare the roots accepted by a log with a local trust store
+#[derive(Parser)]
+#[command(name = "root-review")]
+struct Opts {
+    /// The URL of the log
+    #[clap(short, long)]
+    url: String,
+
+    /// The trust store, a PEM bundle or Mozilla's certdata.txt
+    #[clap(short, long)]
+    trust_store: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let opts = Opts::parse();
+
+    let trust_store = TrustStore::from_file(&opts.trust_store)?;
+    let mut roots = Vec::new();
+    for (index, root) in CTLogV1::new(&opts.url)?
+        .get_roots_decoded()
+        .await?
+        .into_iter()
+        .enumerate()
+    {
+        match root {
+            Ok(root) => roots.push(root),
+            Err(e) => eprintln!("Skipping root {}: {}", index, e),
+        }
+    }
+
+    println!(
+        "{} roots accepted by {}, {} in the trust store",
+        roots.len(),
+        opts.url,
+        trust_store.roots.len()
+    );
+    print!("{}", trust_store.compare(&roots, chrono::Utc::now()));
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use ctlog::ct::v1::sct::verify_embedded_scts;
+use ctlog::loglist::LogList;
+use ctlog::policy::{evaluate, CtPolicy};
+use x509_parser::pem::Pem;
+
+/// Verify the SCTs embedded in a certificate
+#[derive(Parser)]
+#[command(name = "sct-verify")]
+struct Opts {
+    /// The leaf certificate, PEM
+    certificate: PathBuf,
+
+    /// The issuer of the leaf certificate, PEM
+    issuer: PathBuf,
+
+    /// The log list, e.g. Chrome's log_list.json
+    #[clap(short, long)]
+    log_list: PathBuf,
+}
+
+fn read_pem(path: &PathBuf) -> Result<Pem> {
+    let data = std::fs::read(path)?;
+    let (_, pem) = x509_parser::pem::parse_x509_pem(&data)
+        .with_context(|| format!("{} is not PEM", path.display()))?;
+    Ok(pem)
+}
+
+fn main() -> Result<()> {
+    let opts = Opts::parse();
+
+    let certificate = read_pem(&opts.certificate)?;
+    let issuer = read_pem(&opts.issuer)?;
+    let log_list = LogList::from_file(&opts.log_list)?;
+
+    let certificate = certificate.parse_x509()?;
+    let issuer = issuer.parse_x509()?;
+
+    let results = verify_embedded_scts(&certificate, &issuer, &log_list)?;
+    if results.is_empty() {
+        println!("No embedded SCTs");
+    }
+
+    for result in &results {
+        print!("{}", result.sct.display(Some(&log_list)).indent(2));
+        println!("  Status: {}", result.status);
+    }
+
+    for policy in [CtPolicy::Chrome, CtPolicy::Apple] {
+        let evaluation = evaluate(
+            policy,
+            &certificate,
+            &results,
+            &[],
+            &log_list,
+            chrono::Utc::now(),
+        );
+        print!("{}", evaluation);
+    }
+
+    Ok(())
+}
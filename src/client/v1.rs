@@ -1,7 +1,10 @@
 // use anyhow::Result;
+use std::time::Duration;
+
 use reqwest::Client;
 
 use crate::client::CTLog;
+use crate::ct::v1::sct::{SctAuditStatus, SignedCertificateTimestamp};
 use crate::ct::v1::*;
 use crate::CTLogError;
 
@@ -18,6 +21,9 @@ impl CTLogV1 {
 
     /// Add Chain to Log
     ///
+    /// A log rejecting the chain answers with an HTTP error status, which is returned as a
+    /// [ReqwestError](CTLogError::ReqwestError) with a status.
+    ///
     /// [RFC 6962 4.1](https://datatracker.ietf.org/doc/html/rfc6962#section-4.1)
     pub async fn add_chain(&self, chain: Vec<String>) -> Result<AddChainResponse, CTLogError> {
         let url = self.0.log_server.join("ct/v1/add-chain")?;
@@ -25,9 +31,10 @@ impl CTLogV1 {
             .0
             .inner
             .post(url)
-            .json(&chain)
+            .json(&serde_json::json!({ "chain": chain }))
             .send()
             .await?
+            .error_for_status()?
             .json()
             .await?;
         Ok(response)
@@ -35,6 +42,9 @@ impl CTLogV1 {
 
     /// Add PreCertChain to Log
     ///
+    /// A log rejecting the chain answers with an HTTP error status, which is returned as a
+    /// [ReqwestError](CTLogError::ReqwestError) with a status.
+    ///
     /// [RFC 6962 4.2](https://datatracker.ietf.org/doc/html/rfc6962#section-4.2)
     pub async fn add_pre_chain(&self, chain: Vec<String>) -> Result<AddChainResponse, CTLogError> {
         let url = self.0.log_server.join("ct/v1/add-pre-chain")?;
@@ -42,9 +52,10 @@ impl CTLogV1 {
             .0
             .inner
             .post(url)
-            .json(&chain)
+            .json(&serde_json::json!({ "chain": chain }))
             .send()
             .await?
+            .error_for_status()?
             .json()
             .await?;
         Ok(response)
@@ -76,19 +87,119 @@ impl CTLogV1 {
 
     /// Retrieve Merkle Audit Proof from Log by Leaf Hash
     ///
+    /// `hash` is the base64-encoded leaf hash. Logs answer with HTTP 400, or 404 for
+    /// Trillian-based logs, when the hash is not in the tree, which is returned as a
+    /// [ReqwestError](CTLogError::ReqwestError) with a status.
+    ///
     /// [RFC 6962 4.5](https://datatracker.ietf.org/doc/html/rfc6962#section-4.5)
     pub async fn get_proof_by_hash(
         &self,
         hash: &str,
         tree_size: u64,
     ) -> Result<GetProofByHashResponse, CTLogError> {
-        let url = self.0.log_server.join(&format!(
-            "ct/v1/get-proof-by-hash?hash={hash}&tree_size={tree_size}",
-        ))?;
-        let response = self.0.inner.get(url).send().await?.json().await?;
+        let mut url = self.0.log_server.join("ct/v1/get-proof-by-hash")?;
+        url.query_pairs_mut()
+            .append_pair("hash", hash)
+            .append_pair("tree_size", &tree_size.to_string());
+        let response = self
+            .0
+            .inner
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
         Ok(response)
     }
 
+    /// Check that the log incorporated the entry of an SCT within its Maximum Merge Delay
+    ///
+    /// Computes the leaf hash of `entry` as logged for `sct` and asks for an inclusion proof
+    /// in the latest STH, which is verified against the STH root hash once the STH signature
+    /// is verified with `log_key`, the log's DER SubjectPublicKeyInfo. When the log answers
+    /// that the entry is not found, the SCT is [Pending](SctAuditStatus::Pending) until the
+    /// log publishes an STH past `sct.timestamp + mmd`, and an
+    /// [MmdViolation](SctAuditStatus::MmdViolation) after.
+    pub async fn audit_sct(
+        &self,
+        sct: &SignedCertificateTimestamp,
+        entry: &TimestampedEntrySignedInner,
+        log_key: &[u8],
+        mmd: Duration,
+    ) -> Result<SctAuditStatus, CTLogError> {
+        let leaf_hash = sct.leaf_hash(entry)?;
+        let sth = self.get_sth().await?;
+        if !sth.verify_signature(log_key)? {
+            return Err(CTLogError::VerificationError(format!(
+                "signature of the STH of tree size {} does not match the log key",
+                sth.tree_size
+            )));
+        }
+
+        let leaf_index = match self
+            .get_proof_by_hash(&base64::encode(leaf_hash), sth.tree_size)
+            .await
+        {
+            Ok(proof) => {
+                let root_hash = sth.root_hash()?;
+                let audit_path = proof
+                    .audit_path
+                    .iter()
+                    .map(|node| decode_hash(node))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !merkle::verify_inclusion(
+                    &leaf_hash,
+                    proof.leaf_index,
+                    sth.tree_size,
+                    &audit_path,
+                    &root_hash,
+                ) {
+                    return Err(CTLogError::VerificationError(format!(
+                        "inclusion proof for leaf {} does not match the root hash of tree size {}",
+                        proof.leaf_index, sth.tree_size
+                    )));
+                }
+                Some(proof.leaf_index)
+            }
+            // Logs answer 400 Bad Request, or 404 Not Found for Trillian-based logs, for
+            // hashes that are not in the tree
+            Err(CTLogError::ReqwestError(e))
+                if matches!(
+                    e.status(),
+                    Some(reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::NOT_FOUND)
+                ) =>
+            {
+                None
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(SctAuditStatus::classify(
+            sct.timestamp,
+            mmd,
+            &sth,
+            leaf_index,
+        ))
+    }
+
+    /// [Audit](CTLogV1::audit_sct) an SCT every `poll_interval` until it is no longer pending
+    pub async fn wait_for_sct_incorporation(
+        &self,
+        sct: &SignedCertificateTimestamp,
+        entry: &TimestampedEntrySignedInner,
+        log_key: &[u8],
+        mmd: Duration,
+        poll_interval: Duration,
+    ) -> Result<SctAuditStatus, CTLogError> {
+        loop {
+            match self.audit_sct(sct, entry, log_key, mmd).await? {
+                SctAuditStatus::Pending { .. } => tokio::time::sleep(poll_interval).await,
+                status => return Ok(status),
+            }
+        }
+    }
+
     /// Retrieve Entries from Log
     ///
     /// [RFC 6962 4.6](https://datatracker.ietf.org/doc/html/rfc6962#section-4.6)
@@ -107,18 +218,30 @@ impl CTLogV1 {
 
     /// Retrieve Entries from Log and decode them
     ///
-    /// [get_entries](CTLogV1::get_entries)
+    /// Entries of an unknown type are kept as [LogEntry::Unknown](crate::ct::v1::LogEntry).
+    /// Fails on the first entry that does not decode, such as one with an invalid
+    /// certificate. Use [get_entries](CTLogV1::get_entries) and `DecodedEntry::try_from` to
+    /// skip those instead.
     pub async fn get_entries_decoded(
         &self,
         start: u64,
         end: u64,
     ) -> Result<Vec<DecodedEntry>, CTLogError> {
         let entries = self.get_entries(start, end).await?.entries;
-        let decoded_entries = entries
-            .iter()
-            .map(|entry| entry.try_into().unwrap())
-            .collect();
-        Ok(decoded_entries)
+        entries.iter().map(DecodedEntry::try_from).collect()
+    }
+
+    /// Retrieve Entries from Log into a single [EntryBatch](borrowed::EntryBatch)
+    ///
+    /// Cheaper than [get_entries_decoded](CTLogV1::get_entries_decoded) for bulk crawls:
+    /// entries borrow from one buffer and certificates are parsed lazily.
+    pub async fn get_entries_batch(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<borrowed::EntryBatch, CTLogError> {
+        let entries = self.get_entries(start, end).await?.entries;
+        borrowed::EntryBatch::decode(&entries)
     }
 
     /// Retrieve Accepted Root Certificates
@@ -130,6 +253,38 @@ impl CTLogV1 {
         Ok(response)
     }
 
+    /// Retrieve Accepted Root Certificates and decode them
+    ///
+    /// Each root is decoded on its own, in the order of [get_roots](CTLogV1::get_roots), so
+    /// that a root which fails to decode does not hide the others.
+    pub async fn get_roots_decoded(
+        &self,
+    ) -> Result<Vec<Result<WrapX509Certificate, CTLogError>>, CTLogError> {
+        Ok(self
+            .get_roots()
+            .await?
+            .certificates
+            .iter()
+            .map(|certificate| WrapX509Certificate::from_der(&base64::decode(certificate)?))
+            .collect())
+    }
+
+    /// Record the accepted roots, to [diff](roots::RootsSnapshot::diff) with a later snapshot
+    ///
+    /// Roots which fail to decode are left out of the snapshot.
+    pub async fn roots_snapshot(&self) -> Result<roots::RootsSnapshot, CTLogError> {
+        let roots: Vec<_> = self
+            .get_roots_decoded()
+            .await?
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect();
+        Ok(roots::RootsSnapshot::new(
+            self.0.log_server.as_str(),
+            &roots,
+        ))
+    }
+
     /// Retrieve Entry + Merkle Audit Proof from Log
     ///
     /// [RFC 6962 4.8](https://datatracker.ietf.org/doc/html/rfc6962#section-4.8)
@@ -145,3 +300,10 @@ impl CTLogV1 {
         Ok(response)
     }
 }
+
+/// Decode a base64 Merkle Tree hash.
+fn decode_hash(hash: &str) -> Result<[u8; 32], CTLogError> {
+    base64::decode(hash)?
+        .try_into()
+        .map_err(|_| CTLogError::DecodeError("Merkle Tree hash is not 32 bytes".into()))
+}
@@ -1 +1,2 @@
+pub mod oid;
 pub mod v1;
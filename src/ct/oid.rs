@@ -0,0 +1,103 @@
+//! Object identifiers of Certificate Transparency and of the CA/Browser Forum.
+//!
+//! The CT identifiers are those of
+//! [RFC 6962 3.1 and 3.3](https://datatracker.ietf.org/doc/html/rfc6962#section-3.1), the
+//! CA/Browser Forum ones the reserved certificate policy identifiers of the
+//! [Baseline Requirements 7.1.6.1](https://cabforum.org/baseline-requirements-documents/).
+//! [registry] adds them to the `oid_registry` registry, so that
+//! [format_oid](oid_registry::format_oid) names them.
+
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use oid_registry::{Oid, OidEntry, OidRegistry};
+
+pub use oid_registry::OID_CT_LIST_SCT;
+
+/// Precertificate poison extension (1.3.6.1.4.1.11129.2.4.3).
+pub const OID_CT_PRECERT_POISON: Oid<'static> = Oid::new(Cow::Borrowed(&[
+    0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x03,
+]));
+
+/// Precertificate Signing Certificate extended key usage (1.3.6.1.4.1.11129.2.4.4).
+pub const OID_CT_PRECERT_SIGNING_CERT: Oid<'static> = Oid::new(Cow::Borrowed(&[
+    0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x04,
+]));
+
+/// OCSP single response extension carrying an SCT list (1.3.6.1.4.1.11129.2.4.5).
+pub const OID_CT_OCSP_SCT: Oid<'static> = Oid::new(Cow::Borrowed(&[
+    0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x05,
+]));
+
+/// Extended Validation policy (2.23.140.1.1).
+pub const OID_CABF_EV: Oid<'static> = Oid::new(Cow::Borrowed(&[0x67, 0x81, 0x0c, 0x01, 0x01]));
+
+/// Domain Validated policy (2.23.140.1.2.1).
+pub const OID_CABF_DV: Oid<'static> =
+    Oid::new(Cow::Borrowed(&[0x67, 0x81, 0x0c, 0x01, 0x02, 0x01]));
+
+/// Organization Validated policy (2.23.140.1.2.2).
+pub const OID_CABF_OV: Oid<'static> =
+    Oid::new(Cow::Borrowed(&[0x67, 0x81, 0x0c, 0x01, 0x02, 0x02]));
+
+/// Individual Validated policy (2.23.140.1.2.3).
+pub const OID_CABF_IV: Oid<'static> =
+    Oid::new(Cow::Borrowed(&[0x67, 0x81, 0x0c, 0x01, 0x02, 0x03]));
+
+/// The CT and CA/Browser Forum identifiers: OID, short name and description.
+pub const CT_OIDS: [(Oid<'static>, &str, &str); 8] = [
+    (
+        OID_CT_LIST_SCT,
+        "ctSCTList",
+        "Certificate Transparency Signed Certificate Timestamp List",
+    ),
+    (
+        OID_CT_PRECERT_POISON,
+        "ctPrecertPoison",
+        "Certificate Transparency Precertificate Poison",
+    ),
+    (
+        OID_CT_PRECERT_SIGNING_CERT,
+        "ctPrecertSigning",
+        "Certificate Transparency Precertificate Signing Certificate",
+    ),
+    (
+        OID_CT_OCSP_SCT,
+        "ctOCSPSCTList",
+        "Certificate Transparency OCSP Signed Certificate Timestamp List",
+    ),
+    (
+        OID_CABF_EV,
+        "ev-guidelines",
+        "CA/B Forum Extended Validation",
+    ),
+    (
+        OID_CABF_DV,
+        "domain-validated",
+        "CA/B Forum Domain Validated",
+    ),
+    (
+        OID_CABF_OV,
+        "organization-validated",
+        "CA/B Forum Organization Validated",
+    ),
+    (
+        OID_CABF_IV,
+        "individual-validated",
+        "CA/B Forum Individual Validated",
+    ),
+];
+
+/// The registry with all crypto and X.509 identifiers of `oid_registry`, and [CT_OIDS].
+///
+/// Built on first use.
+pub fn registry() -> &'static OidRegistry<'static> {
+    static REGISTRY: OnceLock<OidRegistry<'static>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = OidRegistry::default().with_all_crypto().with_x509();
+        for (oid, sn, description) in CT_OIDS {
+            registry.insert(oid, OidEntry::new(sn, description));
+        }
+        registry
+    })
+}
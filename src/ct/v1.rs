@@ -1,17 +1,35 @@
 use std::fmt;
+use std::sync::Arc;
 
-use chrono::TimeZone;
 use deku::prelude::*;
-use oid_registry::{format_oid, OidRegistry};
+use oid_registry::format_oid;
 use ouroboros::self_referencing;
 use serde::{Deserialize, Serialize};
+use x509_parser::num_bigint::BigUint;
 use x509_parser::prelude::*;
 
+use self::classification::ValidationLevel;
 use crate::{
-    utils::{print_x509_extension, print_x509_ski},
+    ct::oid,
+    utils::{
+        datetime_from_millis, encode_pem, format_fingerprint, print_hex_rows, print_x509_extension,
+        print_x509_ski, read_pem_certificates, sha1, sha256,
+    },
     CTLogError,
 };
 
+pub mod borrowed;
+pub mod classification;
+pub mod extensions;
+pub mod linking;
+pub mod merkle;
+pub mod precert;
+pub mod render;
+pub mod roots;
+pub mod sct;
+pub mod summary;
+pub mod validation;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AddChainResponse {
     /// The version of the SignedCertificateTimestamp
@@ -53,6 +71,37 @@ pub struct GetSthResponse {
     pub tree_head_signature: String,
 }
 
+impl GetSthResponse {
+    /// The decoded Merkle Tree hash of the tree.
+    pub fn root_hash(&self) -> Result<[u8; 32], CTLogError> {
+        base64::decode(&self.sha256_root_hash)?
+            .try_into()
+            .map_err(|_| CTLogError::DecodeError("Merkle Tree hash is not 32 bytes".into()))
+    }
+
+    /// The TreeHeadSignature data signed by the log.
+    ///
+    /// [RFC 6962 3.5](https://datatracker.ietf.org/doc/html/rfc6962#section-3.5)
+    pub fn signed_data(&self) -> Result<Vec<u8>, CTLogError> {
+        let mut data = vec![
+            Version::V1.value(),
+            // SignatureType tree_hash
+            1,
+        ];
+        data.extend_from_slice(&self.timestamp.to_be_bytes());
+        data.extend_from_slice(&self.tree_size.to_be_bytes());
+        data.extend_from_slice(&self.root_hash()?);
+        Ok(data)
+    }
+
+    /// Verify the tree head signature with the log's DER SubjectPublicKeyInfo.
+    pub fn verify_signature(&self, log_key: &[u8]) -> Result<bool, CTLogError> {
+        let signature = base64::decode(&self.tree_head_signature)?;
+        let (_, signature) = sct::DigitallySigned::from_bytes((&signature, 0))?;
+        signature.verify(&self.signed_data()?, log_key)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetSthConsistencyResponse {
     /// An array of Merkle Tree nodes, base64 encoded.
@@ -121,11 +170,48 @@ pub struct WrapX509Certificate {
 
 impl WrapX509Certificate {
     pub fn from_bytes(v: &[u8]) -> Result<Self, DekuError> {
-        Ok(WrapX509CertificateBuilder {
-            raw: v.to_vec(),
-            certificate_builder: |raw: &Vec<u8>| X509Certificate::from_der(raw).unwrap().1,
+        Self::from_der(v).map_err(|e| DekuError::Parse(e.to_string()))
+    }
+
+    /// Parse a DER certificate, failing instead of panicking on invalid input.
+    pub fn from_der(der: &[u8]) -> Result<Self, CTLogError> {
+        Ok(WrapX509CertificateTryBuilder {
+            raw: der.to_vec(),
+            certificate_builder: |raw: &Vec<u8>| {
+                X509Certificate::from_der(raw)
+                    .map(|(_, certificate)| certificate)
+                    .map_err(X509Error::from)
+            },
         }
-        .build())
+        .try_build()?)
+    }
+
+    /// The SHA-1 fingerprint of the certificate.
+    pub fn sha1_fingerprint(&self) -> [u8; 20] {
+        sha1(self.as_der())
+    }
+
+    /// The SHA-256 fingerprint of the certificate.
+    pub fn sha256_fingerprint(&self) -> [u8; 32] {
+        sha256(self.as_der())
+    }
+
+    /// The DER encoding of the certificate.
+    pub fn as_der(&self) -> &[u8] {
+        self.borrow_raw()
+    }
+
+    /// Parse the first `CERTIFICATE` of a PEM bundle.
+    pub fn from_pem(pem: &[u8]) -> Result<Self, CTLogError> {
+        match read_pem_certificates(pem)?.first() {
+            Some(der) => Self::from_der(der),
+            None => Err(CTLogError::DecodeError("no certificate in PEM".into())),
+        }
+    }
+
+    /// The certificate as a PEM `CERTIFICATE`.
+    pub fn to_pem(&self) -> String {
+        encode_pem("CERTIFICATE", self.as_der())
     }
 }
 
@@ -144,10 +230,7 @@ impl fmt::Display for WrapX509Certificate {
         writeln!(
             f,
             "  Signature Algorithm: {}",
-            format_oid(
-                certificate.signature_algorithm.oid(),
-                &OidRegistry::default().with_all_crypto()
-            )
+            format_oid(certificate.signature_algorithm.oid(), oid::registry())
         )?;
         writeln!(f, "    Issuer: {}", certificate.issuer())?;
         writeln!(f, "    Validity:")?;
@@ -164,7 +247,15 @@ impl fmt::Display for WrapX509Certificate {
             }
         }
 
-        Ok(())
+        if let Some(level) = self.validation_level() {
+            writeln!(f, "  Validation Level: {}", level)?;
+        }
+
+        fmt_identifiers(
+            f,
+            self,
+            Some((&self.sha1_fingerprint(), &self.sha256_fingerprint())),
+        )
     }
 }
 
@@ -179,11 +270,19 @@ pub struct WrapTbsCertificate {
 
 impl WrapTbsCertificate {
     pub fn from_bytes(v: &[u8]) -> Result<Self, DekuError> {
-        Ok(WrapTbsCertificateBuilder {
+        WrapTbsCertificateTryBuilder {
             raw: v.to_vec(),
-            certificate_builder: |raw: &Vec<u8>| TbsCertificate::from_der(raw).unwrap().1,
+            certificate_builder: |raw: &Vec<u8>| {
+                TbsCertificate::from_der(raw).map(|(_, certificate)| certificate)
+            },
         }
-        .build())
+        .try_build()
+        .map_err(|e| DekuError::Parse(e.to_string()))
+    }
+
+    /// The DER encoding of the TBSCertificate.
+    pub fn as_der(&self) -> &[u8] {
+        self.borrow_raw()
     }
 }
 
@@ -203,10 +302,7 @@ impl fmt::Display for WrapTbsCertificate {
         writeln!(
             f,
             "  Signature Algorithm: {}",
-            format_oid(
-                certificate.signature.oid(),
-                &OidRegistry::default().with_all_crypto()
-            )
+            format_oid(certificate.signature.oid(), oid::registry())
         )?;
         writeln!(f, "    Issuer: {}", certificate.issuer())?;
         writeln!(f, "    Validity:")?;
@@ -223,15 +319,45 @@ impl fmt::Display for WrapTbsCertificate {
             }
         }
 
-        Ok(())
+        if let Some(level) = self.validation_level() {
+            writeln!(f, "  Validation Level: {}", level)?;
+        }
+
+        fmt_identifiers(f, self, None)
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, DekuRead)]
+#[derive(Debug, PartialEq, Eq, Clone, DekuRead, Serialize, Deserialize)]
 #[deku(type = "u16", endian = "big")]
 pub enum LogEntryType {
-    X509Entry = 0,
-    PrecertEntry = 1,
+    #[deku(id = "0")]
+    X509Entry,
+    #[deku(id = "1")]
+    PrecertEntry,
+    /// An entry type this crate does not know.
+    #[deku(id_pat = "_")]
+    Unknown(u16),
+}
+
+impl LogEntryType {
+    /// The TLS encoding of the entry type.
+    pub fn value(&self) -> u16 {
+        match self {
+            LogEntryType::X509Entry => 0,
+            LogEntryType::PrecertEntry => 1,
+            LogEntryType::Unknown(value) => *value,
+        }
+    }
+}
+
+impl From<u16> for LogEntryType {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => LogEntryType::X509Entry,
+            1 => LogEntryType::PrecertEntry,
+            value => LogEntryType::Unknown(value),
+        }
+    }
 }
 
 #[derive(Debug, DekuRead)]
@@ -240,7 +366,7 @@ pub struct ASN1Cert {
     pub length: u32,
     #[deku(
         count = "length",
-        map = "|v: &[u8]| -> Result<_, DekuError> { Ok(Box::new(WrapX509Certificate::from_bytes(v).unwrap())) }"
+        map = "|v: &[u8]| -> Result<_, DekuError> { WrapX509Certificate::from_bytes(v).map(Box::new) }"
     )]
     pub certificate: Box<WrapX509Certificate>,
 }
@@ -251,6 +377,46 @@ impl fmt::Display for ASN1Cert {
     }
 }
 
+impl ASN1Cert {
+    /// Wrap a DER certificate, e.g. the leaf of a TLS handshake.
+    pub fn from_der(der: &[u8]) -> Result<Self, CTLogError> {
+        Ok(Self {
+            length: der.len() as u32,
+            certificate: Box::new(WrapX509Certificate::from_der(der)?),
+        })
+    }
+
+    /// Wrap the first `CERTIFICATE` of a PEM bundle.
+    pub fn from_pem(pem: &[u8]) -> Result<Self, CTLogError> {
+        let certificate = WrapX509Certificate::from_pem(pem)?;
+        Ok(Self {
+            length: certificate.as_der().len() as u32,
+            certificate: Box::new(certificate),
+        })
+    }
+
+    /// The DER encoding of the certificate.
+    pub fn as_der(&self) -> &[u8] {
+        self.certificate.as_der()
+    }
+
+    pub fn to_pem(&self) -> String {
+        self.certificate.to_pem()
+    }
+
+    /// Print subject, issuer and SHA-256 fingerprint of the certificate.
+    fn fmt_summary(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        writeln!(f, "{:indent$}Subject: {}", "", self.subject())?;
+        writeln!(f, "{:indent$}Issuer: {}", "", self.issuer())?;
+        writeln!(
+            f,
+            "{:indent$}SHA256 Fingerprint: {}",
+            "",
+            format_fingerprint(&self.certificate.sha256_fingerprint())
+        )
+    }
+}
+
 #[derive(Debug, DekuRead)]
 pub struct ASN1CertChain {
     #[deku(bytes = 3, endian = "big")]
@@ -259,20 +425,148 @@ pub struct ASN1CertChain {
     pub certificates: Vec<ASN1Cert>,
 }
 
+impl ASN1CertChain {
+    /// A chain of DER certificates, issuer first.
+    pub fn from_der_certificates<T: AsRef<[u8]>>(certificates: &[T]) -> Result<Self, CTLogError> {
+        let certificates = certificates
+            .iter()
+            .map(|der| ASN1Cert::from_der(der.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            length: certificates.iter().map(|c| 3 + c.length).sum(),
+            certificates,
+        })
+    }
+
+    /// Read every `CERTIFICATE` of a PEM bundle, issuer first.
+    pub fn from_pem(bundle: &[u8]) -> Result<Self, CTLogError> {
+        Self::from_der_certificates(&read_pem_certificates(bundle)?)
+    }
+
+    /// The chain as a PEM bundle.
+    pub fn to_pem(&self) -> String {
+        self.certificates.iter().map(ASN1Cert::to_pem).collect()
+    }
+
+    /// The DER certificates.
+    pub fn to_der_certificates(&self) -> Vec<Vec<u8>> {
+        self.certificates
+            .iter()
+            .map(|certificate| certificate.as_der().to_vec())
+            .collect()
+    }
+
+    /// The base64 DER certificates, as [add_chain](crate::CTLogV1::add_chain) takes them.
+    pub fn to_base64(&self) -> Vec<String> {
+        self.certificates
+            .iter()
+            .map(|certificate| base64::encode(certificate.as_der()))
+            .collect()
+    }
+
+    /// Index of the first certificate in the chain whose subject is `issuer`.
+    pub fn find_issuer(&self, issuer: &X509Name) -> Option<usize> {
+        self.certificates
+            .iter()
+            .position(|certificate| certificate.subject().as_raw() == issuer.as_raw())
+    }
+
+    /// Describe which certificate of the chain signed a certificate issued by `issuer`.
+    ///
+    /// Issuers are matched by name; signatures are not verified here.
+    fn signer(&self, issuer: &X509Name) -> String {
+        match self.find_issuer(issuer) {
+            Some(index) => format!("chain[{index}]"),
+            None => "<not in chain>".to_string(),
+        }
+    }
+
+    /// Print every certificate of the chain with a marker on its signer.
+    fn fmt_chain(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{:indent$}Chain ({} certificates):",
+            "",
+            self.certificates.len()
+        )?;
+        for (index, certificate) in self.certificates.iter().enumerate() {
+            writeln!(f, "{:indent$}chain[{index}]:", "", indent = indent + 2)?;
+            certificate.fmt_summary(f, indent + 4)?;
+            let signer = if certificate.subject().as_raw() == certificate.issuer().as_raw() {
+                "itself (self-signed)".to_string()
+            } else {
+                self.signer(certificate.issuer())
+            };
+            writeln!(f, "{:indent$}Signed by: {signer}", "", indent = indent + 4)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, DekuRead)]
 pub struct PrecertChainEntry {
     pub pre_certificate: ASN1Cert,
     pub precertificate_chain: ASN1CertChain,
 }
 
+impl PrecertChainEntry {
+    /// Read a PEM bundle: the precertificate, then its chain.
+    pub fn from_pem(bundle: &[u8]) -> Result<Self, CTLogError> {
+        let certificates = read_pem_certificates(bundle)?;
+        let (pre_certificate, chain) = certificates
+            .split_first()
+            .ok_or_else(|| CTLogError::DecodeError("no certificate in PEM".into()))?;
+        Ok(Self {
+            pre_certificate: ASN1Cert::from_der(pre_certificate)?,
+            precertificate_chain: ASN1CertChain::from_der_certificates(chain)?,
+        })
+    }
+
+    /// The precertificate and its chain as a PEM bundle.
+    pub fn to_pem(&self) -> String {
+        self.pre_certificate.to_pem() + &self.precertificate_chain.to_pem()
+    }
+
+    /// The base64 DER precertificate and chain, as
+    /// [add_pre_chain](crate::CTLogV1::add_pre_chain) takes them.
+    pub fn to_base64(&self) -> Vec<String> {
+        let mut chain = vec![base64::encode(self.pre_certificate.as_der())];
+        chain.extend(self.precertificate_chain.to_base64());
+        chain
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, DekuRead)]
 #[deku(type = "u8")]
 pub enum Version {
-    V1 = 0,
+    #[deku(id = "0")]
+    V1,
+    /// A version this crate does not know.
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+impl Version {
+    /// The TLS encoding of the version.
+    pub fn value(&self) -> u8 {
+        match self {
+            Version::V1 => 0,
+            Version::Unknown(value) => *value,
+        }
+    }
+}
+
+impl From<u8> for Version {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Version::V1,
+            value => Version::Unknown(value),
+        }
+    }
 }
 
 #[derive(Debug, DekuRead)]
-pub struct IssuerKeyHash([u8; 32]);
+pub struct IssuerKeyHash(pub [u8; 32]);
 
 impl fmt::LowerHex for IssuerKeyHash {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -290,7 +584,7 @@ pub struct PreCert {
     pub length: u32,
     #[deku(
         count = "length",
-        map = "|v: &[u8]| -> Result<_, DekuError> { Ok(Box::new(WrapTbsCertificate::from_bytes(v).unwrap())) }"
+        map = "|v: &[u8]| -> Result<_, DekuError> { WrapTbsCertificate::from_bytes(v).map(Box::new) }"
     )]
     pub tbs_certificate: Box<WrapTbsCertificate>,
 }
@@ -312,105 +606,596 @@ pub struct CtExtensions {
 #[derive(Debug, PartialEq, Eq, Clone, DekuRead)]
 #[deku(type = "u8")]
 pub enum MerkleLeafType {
-    TimestampedEntry = 0,
+    #[deku(id = "0")]
+    TimestampedEntry,
+    /// A leaf type this crate does not know.
+    #[deku(id_pat = "_")]
+    Unknown(u8),
 }
 
+impl MerkleLeafType {
+    /// The TLS encoding of the leaf type.
+    pub fn value(&self) -> u8 {
+        match self {
+            MerkleLeafType::TimestampedEntry => 0,
+            MerkleLeafType::Unknown(value) => *value,
+        }
+    }
+}
+
+impl From<u8> for MerkleLeafType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => MerkleLeafType::TimestampedEntry,
+            value => MerkleLeafType::Unknown(value),
+        }
+    }
+}
+
+/// The signed entry of a [TimestampedEntry], identified by its [LogEntryType].
 #[derive(Debug, DekuRead)]
-#[deku(ctx = "entry_type: LogEntryType", id = "entry_type")]
+#[deku(type = "LogEntryType")]
 pub enum TimestampedEntrySignedInner {
     #[deku(id = "LogEntryType::X509Entry")]
     X509(ASN1Cert),
     #[deku(id = "LogEntryType::PrecertEntry")]
     Precert(PreCert),
+    /// An entry of an unknown type. Its length is unknown, so nothing after the type is
+    /// read.
+    #[deku(id_pat = "_")]
+    Unknown(LogEntryType),
+}
+
+impl TimestampedEntrySignedInner {
+    pub fn entry_type(&self) -> LogEntryType {
+        match self {
+            TimestampedEntrySignedInner::X509(_) => LogEntryType::X509Entry,
+            TimestampedEntrySignedInner::Precert(_) => LogEntryType::PrecertEntry,
+            TimestampedEntrySignedInner::Unknown(entry_type) => entry_type.clone(),
+        }
+    }
+
+    /// TLS encoding of the entry type and signed entry, as covered by an SCT signature.
+    ///
+    /// Fails for [Unknown](TimestampedEntrySignedInner::Unknown) entries, whose signed entry
+    /// is not decoded.
+    pub fn encode(&self) -> Result<Vec<u8>, CTLogError> {
+        let mut output = Vec::new();
+        match self {
+            TimestampedEntrySignedInner::X509(certificate) => {
+                let der = certificate.certificate.as_der();
+                output.extend_from_slice(&LogEntryType::X509Entry.value().to_be_bytes());
+                output.extend_from_slice(&(der.len() as u32).to_be_bytes()[1..]);
+                output.extend_from_slice(der);
+            }
+            TimestampedEntrySignedInner::Precert(pre_cert) => {
+                let der = pre_cert.tbs_certificate.as_der();
+                output.extend_from_slice(&LogEntryType::PrecertEntry.value().to_be_bytes());
+                output.extend_from_slice(&pre_cert.issuer_key_hash.0);
+                output.extend_from_slice(&(der.len() as u32).to_be_bytes()[1..]);
+                output.extend_from_slice(der);
+            }
+            TimestampedEntrySignedInner::Unknown(entry_type) => {
+                return Err(CTLogError::DecodeError(format!(
+                    "cannot encode a signed entry of unknown type {}",
+                    entry_type.value()
+                )));
+            }
+        }
+        Ok(output)
+    }
 }
 
 #[derive(Debug, DekuRead)]
 pub struct TimestampedEntry {
     #[deku(endian = "big")]
     pub timestamp: u64,
-    pub entry_type: LogEntryType,
-    #[deku(ctx = "entry_type.clone()")]
     pub signed_entry: TimestampedEntrySignedInner,
-    pub extensions: CtExtensions,
+    /// `None` for unknown entry types, as the end of their signed entry is unknown.
+    #[deku(cond = "!matches!(signed_entry, TimestampedEntrySignedInner::Unknown(_))")]
+    pub extensions: Option<CtExtensions>,
 }
 
+impl TimestampedEntry {
+    pub fn entry_type(&self) -> LogEntryType {
+        self.signed_entry.entry_type()
+    }
+}
+
+/// A leaf of the log's Merkle tree.
+///
+/// Only v1 `timestamped_entry` leaves are defined; nothing is read after an unknown version
+/// or leaf type.
 #[derive(Debug, DekuRead)]
 pub struct MerkleTreeLeaf {
     pub version: Version,
     pub leaf_type: MerkleLeafType,
-    pub timestamped_entry: TimestampedEntry,
+    /// `None` for unknown versions and leaf types.
+    #[deku(cond = "*version == Version::V1 && *leaf_type == MerkleLeafType::TimestampedEntry")]
+    pub timestamped_entry: Option<TimestampedEntry>,
 }
 
+/// Accessors shared by X.509 certificates and precertificate TBSCertificates.
+///
+/// [X509Certificate] dereferences to its [TbsCertificate], so everything is read from there.
+pub trait CertificateInfo {
+    fn tbs_certificate(&self) -> &TbsCertificate<'_>;
+
+    fn subject(&self) -> &X509Name<'_> {
+        self.tbs_certificate().subject()
+    }
+
+    fn issuer(&self) -> &X509Name<'_> {
+        self.tbs_certificate().issuer()
+    }
+
+    /// The names of the subjectAltName extension, empty if absent or invalid.
+    fn subject_alternative_names(&self) -> Vec<&GeneralName<'_>> {
+        match self.tbs_certificate().subject_alternative_name() {
+            Ok(Some(san)) => san.value.general_names.iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn serial(&self) -> &BigUint {
+        &self.tbs_certificate().serial
+    }
+
+    /// The serial number, colon-separated hex.
+    fn raw_serial_as_string(&self) -> String {
+        self.tbs_certificate().raw_serial_as_string()
+    }
+
+    fn validity(&self) -> &Validity {
+        self.tbs_certificate().validity()
+    }
+
+    fn extensions(&self) -> &[X509Extension<'_>] {
+        self.tbs_certificate().extensions()
+    }
+
+    fn public_key(&self) -> &SubjectPublicKeyInfo<'_> {
+        self.tbs_certificate().public_key()
+    }
+
+    /// SHA-256 of the DER SubjectPublicKeyInfo, the `pin-sha256` of key pinning.
+    fn spki_sha256(&self) -> [u8; 32] {
+        sha256(self.public_key().raw)
+    }
+
+    /// The key identifier of the subjectKeyIdentifier extension, if present.
+    fn subject_key_identifier(&self) -> Option<&[u8]> {
+        self.extensions()
+            .iter()
+            .find_map(|extension| match extension.parsed_extension() {
+                ParsedExtension::SubjectKeyIdentifier(identifier) => Some(identifier.0),
+                _ => None,
+            })
+    }
+
+    /// SHA-256 of the DER TBSCertificate.
+    fn tbs_sha256(&self) -> [u8; 32] {
+        sha256(self.tbs_certificate().as_ref())
+    }
+
+    /// The validation level, see [classify](classification::classify).
+    fn validation_level(&self) -> Option<ValidationLevel> {
+        classification::classify(self.tbs_certificate())
+    }
+}
+
+/// Print the identifiers of a certificate, with its fingerprints when it is a full
+/// certificate rather than a TBSCertificate.
+fn fmt_identifiers(
+    f: &mut fmt::Formatter<'_>,
+    certificate: &impl CertificateInfo,
+    fingerprints: Option<(&[u8], &[u8])>,
+) -> fmt::Result {
+    writeln!(f, "  Identifiers:")?;
+    if let Some((sha1, sha256)) = fingerprints {
+        writeln!(f, "    SHA1 Fingerprint: {}", format_fingerprint(sha1))?;
+        writeln!(f, "    SHA256 Fingerprint: {}", format_fingerprint(sha256))?;
+    }
+    writeln!(
+        f,
+        "    SPKI SHA256: {}",
+        base64::encode(certificate.spki_sha256())
+    )?;
+    if let Some(identifier) = certificate.subject_key_identifier() {
+        writeln!(
+            f,
+            "    Subject Key Identifier: {}",
+            format_fingerprint(identifier)
+        )?;
+    }
+    writeln!(
+        f,
+        "    TBS SHA256: {}",
+        format_fingerprint(&certificate.tbs_sha256())
+    )
+}
+
+impl CertificateInfo for WrapX509Certificate {
+    fn tbs_certificate(&self) -> &TbsCertificate<'_> {
+        &self.borrow_certificate().tbs_certificate
+    }
+}
+
+impl CertificateInfo for WrapTbsCertificate {
+    fn tbs_certificate(&self) -> &TbsCertificate<'_> {
+        self.borrow_certificate()
+    }
+}
+
+impl CertificateInfo for ASN1Cert {
+    fn tbs_certificate(&self) -> &TbsCertificate<'_> {
+        self.certificate.tbs_certificate()
+    }
+}
+
+impl CertificateInfo for PreCert {
+    fn tbs_certificate(&self) -> &TbsCertificate<'_> {
+        self.tbs_certificate.tbs_certificate()
+    }
+}
+
+/// The signed entry of a log entry together with its matching `extra_data`.
 #[derive(Debug)]
-pub enum DecodedEntryInner {
-    X509(ASN1CertChain),
-    Precert(PrecertChainEntry),
+pub enum LogEntry {
+    X509 {
+        certificate: ASN1Cert,
+        chain: ASN1CertChain,
+    },
+    Precert {
+        pre_cert: PreCert,
+        chain: PrecertChainEntry,
+    },
+    /// An entry of an unknown version, leaf type or entry type, kept undecoded.
+    Unknown {
+        /// `None` when the version or leaf type is unknown.
+        entry_type: Option<LogEntryType>,
+        /// The MerkleTreeLeaf.
+        leaf_input: Vec<u8>,
+        extra_data: Vec<u8>,
+    },
 }
 
 /// A structure representing a log entry (parsed from the response of /ct/v1/get-entries).
-#[derive(Debug)]
+///
+/// The parsed certificates are shared behind an [Arc]: cloning an entry does not copy or
+/// re-parse them, and entries can be sent to other threads or kept in caches.
+///
+/// Entries of an unknown [Version], [MerkleLeafType] or [LogEntryType] are kept as
+/// [LogEntry::Unknown].
+#[derive(Debug, Clone)]
 pub struct DecodedEntry {
-    pub leaf: MerkleTreeLeaf,
-    pub extra_data: DecodedEntryInner,
+    pub version: Version,
+    pub leaf_type: MerkleLeafType,
+    /// `None` for unknown versions and leaf types.
+    pub timestamp: Option<u64>,
+    pub entry: Arc<LogEntry>,
+    /// `None` for unknown entries.
+    pub extensions: Option<CtExtensions>,
+}
+
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<DecodedEntry>();
+};
+
+impl DecodedEntry {
+    /// `None` for unknown versions and leaf types.
+    pub fn entry_type(&self) -> Option<LogEntryType> {
+        match &*self.entry {
+            LogEntry::X509 { .. } => Some(LogEntryType::X509Entry),
+            LogEntry::Precert { .. } => Some(LogEntryType::PrecertEntry),
+            LogEntry::Unknown { entry_type, .. } => entry_type.clone(),
+        }
+    }
+
+    pub fn is_precert(&self) -> bool {
+        self.entry_type() == Some(LogEntryType::PrecertEntry)
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(*self.entry, LogEntry::Unknown { .. })
+    }
+
+    /// The [CertificateInfo] of the logged certificate: the X.509 leaf, or the TBSCertificate
+    /// of a precert entry. `None` for unknown entries.
+    ///
+    /// Replaces the `CertificateInfo` implementation of `DecodedEntry`, which cannot answer
+    /// for unknown entries: `entry.subject()` becomes `entry.info()?.subject()`.
+    pub fn info(&self) -> Option<&dyn CertificateInfo> {
+        match &*self.entry {
+            LogEntry::X509 { certificate, .. } => Some(certificate),
+            LogEntry::Precert { pre_cert, .. } => Some(pre_cert),
+            LogEntry::Unknown { .. } => None,
+        }
+    }
+
+    /// The certificate submitted to the log: the X.509 leaf, or the precertificate
+    /// (with its poison extension) of a precert entry. `None` for unknown entries.
+    pub fn submitted_certificate(&self) -> Option<&ASN1Cert> {
+        match &*self.entry {
+            LogEntry::X509 { certificate, .. } => Some(certificate),
+            LogEntry::Precert { chain, .. } => Some(&chain.pre_certificate),
+            LogEntry::Unknown { .. } => None,
+        }
+    }
+
+    /// The chain of the submitted certificate, issuer first. `None` for unknown entries.
+    pub fn chain(&self) -> Option<&ASN1CertChain> {
+        match &*self.entry {
+            LogEntry::X509 { chain, .. } => Some(chain),
+            LogEntry::Precert { chain, .. } => Some(&chain.precertificate_chain),
+            LogEntry::Unknown { .. } => None,
+        }
+    }
 }
 
 impl TryFrom<&Entry> for DecodedEntry {
     type Error = CTLogError;
 
     fn try_from(entry: &Entry) -> Result<Self, CTLogError> {
-        let leaf = MerkleTreeLeaf::from_bytes((&base64::decode(entry.leaf_input.clone())?, 0))?.1;
+        let leaf_input = base64::decode(&entry.leaf_input)?;
+        let extra_data = base64::decode(&entry.extra_data)?;
+        let MerkleTreeLeaf {
+            version,
+            leaf_type,
+            timestamped_entry,
+        } = MerkleTreeLeaf::from_bytes((&leaf_input, 0))?.1;
 
-        let extra_data = match leaf.timestamped_entry.entry_type {
-            LogEntryType::X509Entry => {
-                let cert_chain =
-                    ASN1CertChain::from_bytes((&base64::decode(&entry.extra_data)?, 0))?.1;
-                DecodedEntryInner::X509(cert_chain)
-            }
-            LogEntryType::PrecertEntry => {
-                let precert_chain =
-                    PrecertChainEntry::from_bytes((&base64::decode(&entry.extra_data)?, 0))?.1;
-                DecodedEntryInner::Precert(precert_chain)
-            }
+        let (timestamp, signed_entry, extensions) = match timestamped_entry {
+            Some(TimestampedEntry {
+                timestamp,
+                signed_entry,
+                extensions,
+            }) => (Some(timestamp), Some(signed_entry), extensions),
+            None => (None, None, None),
+        };
+        let entry = match signed_entry {
+            Some(TimestampedEntrySignedInner::X509(certificate)) => LogEntry::X509 {
+                certificate,
+                chain: ASN1CertChain::from_bytes((&extra_data, 0))?.1,
+            },
+            Some(TimestampedEntrySignedInner::Precert(pre_cert)) => LogEntry::Precert {
+                pre_cert,
+                chain: PrecertChainEntry::from_bytes((&extra_data, 0))?.1,
+            },
+            Some(TimestampedEntrySignedInner::Unknown(entry_type)) => LogEntry::Unknown {
+                entry_type: Some(entry_type),
+                leaf_input,
+                extra_data,
+            },
+            None => LogEntry::Unknown {
+                entry_type: None,
+                leaf_input,
+                extra_data,
+            },
         };
 
-        Ok(Self { leaf, extra_data })
+        Ok(Self {
+            version,
+            leaf_type,
+            timestamp,
+            entry: Arc::new(entry),
+            extensions,
+        })
     }
 }
 
 impl fmt::Display for DecodedEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Timestamp={} ({}) ",
-            self.leaf.timestamped_entry.timestamp,
-            chrono::Utc
-                .timestamp_millis_opt(self.leaf.timestamped_entry.timestamp as i64)
-                .unwrap()
-        )?;
+        if let Some(timestamp) = self.timestamp {
+            write!(f, "Timestamp={} ", timestamp)?;
+            if let Some(time) = datetime_from_millis(timestamp) {
+                write!(f, "({}) ", time)?;
+            }
+        }
 
-        match (
-            &self.leaf.timestamped_entry.entry_type,
-            &self.leaf.timestamped_entry.signed_entry,
-        ) {
-            (LogEntryType::X509Entry, TimestampedEntrySignedInner::X509(certificate)) => {
+        match &*self.entry {
+            LogEntry::X509 { certificate, chain } => {
                 writeln!(f, "X.509 certificate:")?;
                 writeln!(f, "{certificate}")?;
 
-                // TODO: print the chain
+                writeln!(f, "  Signed by: {}", chain.signer(certificate.issuer()))?;
+                chain.fmt_chain(f, 0)?;
             }
-            (LogEntryType::PrecertEntry, TimestampedEntrySignedInner::Precert(certificate)) => {
+            LogEntry::Precert { pre_cert, chain } => {
                 writeln!(
                     f,
                     "pre-certificate from issuer with keyhash {:x}:",
-                    certificate.issuer_key_hash
+                    pre_cert.issuer_key_hash
                 )?;
-                writeln!(f, "{certificate}")?;
+                writeln!(f, "{pre_cert}")?;
 
-                // TODO: print the chain
+                let chain_entry = &chain.precertificate_chain;
+                writeln!(f, "Precertificate:")?;
+                chain.pre_certificate.fmt_summary(f, 2)?;
+                writeln!(
+                    f,
+                    "  Signed by: {}",
+                    chain_entry.signer(chain.pre_certificate.issuer())
+                )?;
+                chain_entry.fmt_chain(f, 0)?;
+            }
+            LogEntry::Unknown {
+                entry_type,
+                leaf_input,
+                extra_data,
+            } => {
+                write!(
+                    f,
+                    "unknown entry: version {}, leaf type {}",
+                    self.version.value(),
+                    self.leaf_type.value()
+                )?;
+                if let Some(entry_type) = entry_type {
+                    write!(f, ", entry type {}", entry_type.value())?;
+                }
+                writeln!(f)?;
+                writeln!(f, "  Leaf Input:")?;
+                print_hex_rows(f, leaf_input, 4)?;
+                writeln!(f, "  Extra Data:")?;
+                print_hex_rows(f, extra_data, 4)?;
             }
-            _ => unreachable!(),
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::summary::EntrySummary;
+    use super::*;
+
+    const LOG_KEY: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/data/log_key.der"
+    ));
+
+    fn sth() -> GetSthResponse {
+        serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/sth.json"
+        )))
+        .unwrap()
+    }
+
+    #[test]
+    fn sth_signature() {
+        let signed = sth();
+        assert_eq!(signed.signed_data().unwrap().len(), 2 + 8 + 8 + 32);
+        assert!(signed.verify_signature(LOG_KEY).unwrap());
+
+        let mut tampered = sth();
+        tampered.tree_size += 1;
+        assert!(!tampered.verify_signature(LOG_KEY).unwrap());
+
+        let mut tampered = sth();
+        tampered.sha256_root_hash = base64::encode([0; 32]);
+        assert!(!tampered.verify_signature(LOG_KEY).unwrap());
+
+        let mut invalid = sth();
+        invalid.sha256_root_hash = base64::encode([0; 31]);
+        assert!(invalid.verify_signature(LOG_KEY).is_err());
+    }
+
+    /// The DER of the first certificate of a PEM file of tests/data.
+    pub(crate) fn test_certificate(name: &str) -> Vec<u8> {
+        let pem = std::fs::read(format!(
+            "{}/tests/data/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        ))
+        .unwrap();
+        read_pem_certificates(&pem).unwrap().remove(0)
+    }
+
+    fn decoded_entry(entry: LogEntry) -> DecodedEntry {
+        DecodedEntry {
+            version: Version::V1,
+            leaf_type: MerkleLeafType::TimestampedEntry,
+            timestamp: Some(1667260800000),
+            entry: Arc::new(entry),
+            extensions: None,
+        }
+    }
+
+    /// An X.509 entry of the first of `certificates`, with the others as its chain.
+    pub(crate) fn x509_entry(certificates: &[Vec<u8>]) -> DecodedEntry {
+        decoded_entry(LogEntry::X509 {
+            certificate: ASN1Cert::from_der(&certificates[0]).unwrap(),
+            chain: ASN1CertChain::from_der_certificates(&certificates[1..]).unwrap(),
+        })
+    }
+
+    /// A precert entry logging `pre_cert`, for the first of `certificates` with the others
+    /// as its chain.
+    pub(crate) fn precert_entry(pre_cert: PreCert, certificates: &[Vec<u8>]) -> DecodedEntry {
+        decoded_entry(LogEntry::Precert {
+            pre_cert,
+            chain: PrecertChainEntry {
+                pre_certificate: ASN1Cert::from_der(&certificates[0]).unwrap(),
+                precertificate_chain: ASN1CertChain::from_der_certificates(&certificates[1..])
+                    .unwrap(),
+            },
+        })
+    }
+
+    /// The logged entries with `byte` of the leaf input replaced by `value`.
+    pub(crate) fn crafted_entries(byte: usize, value: u8) -> Vec<Entry> {
+        let response: GetEntriesResponse = serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/benches/data/entries.json"
+        )))
+        .unwrap();
+        response
+            .entries
+            .into_iter()
+            .map(|mut entry| {
+                let mut leaf_input = base64::decode(&entry.leaf_input).unwrap();
+                leaf_input[byte] = value;
+                entry.leaf_input = base64::encode(leaf_input);
+                entry
+            })
+            .collect()
+    }
+
+    fn assert_unknown(entry: &Entry, entry_type: Option<LogEntryType>) {
+        let decoded = DecodedEntry::try_from(entry).unwrap();
+        assert!(decoded.is_unknown());
+        assert_eq!(decoded.entry_type(), entry_type);
+        assert_eq!(decoded.timestamp.is_some(), entry_type.is_some());
+        assert!(decoded.extensions.is_none());
+        assert!(decoded.info().is_none());
+        assert!(decoded.chain().is_none());
+        match &*decoded.entry {
+            LogEntry::Unknown {
+                leaf_input,
+                extra_data,
+                ..
+            } => {
+                assert_eq!(*leaf_input, base64::decode(&entry.leaf_input).unwrap());
+                assert_eq!(*extra_data, base64::decode(&entry.extra_data).unwrap());
+            }
+            other => panic!("expected an unknown entry, got {:?}", other),
+        }
+
+        assert!(decoded.to_string().contains("Extra Data:"));
+        let summary = serde_json::to_value(EntrySummary::from(&decoded)).unwrap();
+        assert_eq!(summary["leaf_input"], entry.leaf_input.as_str());
+        assert_eq!(summary["extra_data"], entry.extra_data.as_str());
+        assert!(summary.get("subject").is_none());
+    }
+
+    #[test]
+    fn unknown_entries() {
+        for entry in crafted_entries(0, 1) {
+            assert_unknown(&entry, None);
+            let decoded = DecodedEntry::try_from(&entry).unwrap();
+            assert_eq!(decoded.version, Version::Unknown(1));
+        }
+        for entry in crafted_entries(1, 1) {
+            assert_unknown(&entry, None);
+            let decoded = DecodedEntry::try_from(&entry).unwrap();
+            assert_eq!(decoded.leaf_type, MerkleLeafType::Unknown(1));
+        }
+        for entry in crafted_entries(11, 7) {
+            assert_unknown(&entry, Some(LogEntryType::Unknown(7)));
+        }
+
+        for entry in crafted_entries(0, 0) {
+            let decoded = DecodedEntry::try_from(&entry).unwrap();
+            assert!(!decoded.is_unknown());
+            let info = decoded.info().unwrap();
+            assert_eq!(
+                info.serial(),
+                decoded.submitted_certificate().unwrap().serial()
+            );
+            assert!(decoded.extensions.is_some());
+        }
+    }
+}
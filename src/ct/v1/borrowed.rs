@@ -0,0 +1,289 @@
+//! Borrowed decoding of `get-entries` batches.
+//!
+//! [DecodedEntry](super::DecodedEntry) copies every structure out of the base64-decoded
+//! buffers and parses all certificates eagerly. For bulk crawls, [EntryBatch] decodes a
+//! whole batch into a single buffer instead, and every [BorrowedEntry] references slices of
+//! it. Certificates are only parsed when [BorrowedEntry::certificate] is called.
+
+use std::cell::OnceCell;
+use std::ops::Range;
+
+use x509_parser::error::X509Error;
+use x509_parser::prelude::*;
+
+use super::{Entry, LogEntryType, MerkleLeafType, Version};
+use crate::CTLogError;
+
+#[derive(Debug, Clone)]
+struct EntrySpan {
+    leaf_input: Range<usize>,
+    extra_data: Range<usize>,
+}
+
+/// A batch of entries (from /ct/v1/get-entries) decoded into one shared buffer.
+#[derive(Debug, Clone, Default)]
+pub struct EntryBatch {
+    buffer: Vec<u8>,
+    spans: Vec<EntrySpan>,
+}
+
+impl EntryBatch {
+    /// Base64-decode all entries into a single buffer.
+    ///
+    /// Only the base64 layer is decoded here; the TLS structures are parsed on
+    /// [get](EntryBatch::get) / [iter](EntryBatch::iter).
+    pub fn decode(entries: &[Entry]) -> Result<Self, CTLogError> {
+        let capacity = entries
+            .iter()
+            .map(|entry| (entry.leaf_input.len() + entry.extra_data.len()) / 4 * 3)
+            .sum();
+        let mut buffer = Vec::with_capacity(capacity);
+        let mut spans = Vec::with_capacity(entries.len());
+
+        for entry in entries {
+            let start = buffer.len();
+            base64::decode_config_buf(&entry.leaf_input, base64::STANDARD, &mut buffer)?;
+            let middle = buffer.len();
+            base64::decode_config_buf(&entry.extra_data, base64::STANDARD, &mut buffer)?;
+            spans.push(EntrySpan {
+                leaf_input: start..middle,
+                extra_data: middle..buffer.len(),
+            });
+        }
+
+        Ok(Self { buffer, spans })
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Decode the entry at `index` of the batch.
+    pub fn get(&self, index: usize) -> Option<Result<BorrowedEntry<'_>, CTLogError>> {
+        self.spans.get(index).map(|span| {
+            BorrowedEntry::parse(
+                &self.buffer[span.leaf_input.clone()],
+                &self.buffer[span.extra_data.clone()],
+            )
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Result<BorrowedEntry<'_>, CTLogError>> {
+        (0..self.len()).filter_map(|index| self.get(index))
+    }
+}
+
+/// The leaf certificate of a [BorrowedEntry], parsed on demand.
+#[derive(Debug)]
+pub enum BorrowedCertificate<'a> {
+    X509(X509Certificate<'a>),
+    Precert(TbsCertificate<'a>),
+}
+
+impl<'a> BorrowedCertificate<'a> {
+    /// The TBSCertificate of the leaf, for both X.509 and precertificate entries.
+    pub fn tbs_certificate(&self) -> &TbsCertificate<'a> {
+        match self {
+            BorrowedCertificate::X509(certificate) => &certificate.tbs_certificate,
+            BorrowedCertificate::Precert(certificate) => certificate,
+        }
+    }
+}
+
+/// A log entry referencing the buffer of an [EntryBatch].
+///
+/// Like [DecodedEntry](super::DecodedEntry), nothing is read after an unknown version, leaf
+/// type or entry type; `leaf_input` and `extra_data` keep the raw entry.
+#[derive(Debug)]
+pub struct BorrowedEntry<'a> {
+    pub version: Version,
+    pub leaf_type: MerkleLeafType,
+    /// `None` for unknown versions and leaf types.
+    pub timestamp: Option<u64>,
+    /// `None` for unknown versions and leaf types.
+    pub entry_type: Option<LogEntryType>,
+
+    /// SHA-256 hash of the issuer's public key. Only present for precertificate entries.
+    pub issuer_key_hash: Option<&'a [u8; 32]>,
+
+    /// DER of the leaf: an X.509 certificate, or the TBSCertificate of a precertificate.
+    /// Empty for unknown entries.
+    pub leaf: &'a [u8],
+
+    /// The raw CtExtensions of the TimestampedEntry. `None` for unknown entries.
+    pub extensions: Option<&'a [u8]>,
+
+    /// DER of the submitted precertificate. Only present for precertificate entries.
+    pub pre_certificate: Option<&'a [u8]>,
+
+    /// DER of the certificates in the chain, leaf issuer first. Empty for unknown entries.
+    pub chain: Vec<&'a [u8]>,
+
+    /// The MerkleTreeLeaf.
+    pub leaf_input: &'a [u8],
+    pub extra_data: &'a [u8],
+
+    certificate: OnceCell<Result<BorrowedCertificate<'a>, X509Error>>,
+}
+
+impl<'a> BorrowedEntry<'a> {
+    fn parse(leaf_input: &'a [u8], extra_data: &'a [u8]) -> Result<Self, CTLogError> {
+        let mut entry = Self {
+            version: Version::V1,
+            leaf_type: MerkleLeafType::TimestampedEntry,
+            timestamp: None,
+            entry_type: None,
+            issuer_key_hash: None,
+            leaf: &[],
+            extensions: None,
+            pre_certificate: None,
+            chain: Vec::new(),
+            leaf_input,
+            extra_data,
+            certificate: OnceCell::new(),
+        };
+
+        let mut leaf_input = TlsReader(leaf_input);
+        entry.version = Version::from(leaf_input.u8()?);
+        entry.leaf_type = MerkleLeafType::from(leaf_input.u8()?);
+        if entry.version != Version::V1 || entry.leaf_type != MerkleLeafType::TimestampedEntry {
+            return Ok(entry);
+        }
+        entry.timestamp = Some(u64::from_be_bytes(leaf_input.array()?));
+        let entry_type = LogEntryType::from(u16::from_be_bytes(leaf_input.array()?));
+        entry.entry_type = Some(entry_type.clone());
+
+        let is_precert = match entry_type {
+            LogEntryType::X509Entry => false,
+            LogEntryType::PrecertEntry => true,
+            LogEntryType::Unknown(_) => return Ok(entry),
+        };
+        if is_precert {
+            entry.issuer_key_hash = Some(leaf_input.array_ref()?);
+        }
+        entry.leaf = leaf_input.opaque(3)?;
+        entry.extensions = Some(leaf_input.opaque(2)?);
+
+        let mut extra_data = TlsReader(extra_data);
+        if is_precert {
+            entry.pre_certificate = Some(extra_data.opaque(3)?);
+        }
+        let mut certificates = TlsReader(extra_data.opaque(3)?);
+        while !certificates.0.is_empty() {
+            entry.chain.push(certificates.opaque(3)?);
+        }
+
+        Ok(entry)
+    }
+
+    /// Parse the leaf certificate on first access. `None` for unknown entries.
+    pub fn certificate(&self) -> Result<Option<&BorrowedCertificate<'a>>, CTLogError> {
+        let is_precert = match self.entry_type {
+            Some(LogEntryType::X509Entry) => false,
+            Some(LogEntryType::PrecertEntry) => true,
+            _ => return Ok(None),
+        };
+        self.certificate
+            .get_or_init(|| {
+                if is_precert {
+                    TbsCertificate::from_der(self.leaf)
+                        .map(|(_, certificate)| BorrowedCertificate::Precert(certificate))
+                        .map_err(X509Error::from)
+                } else {
+                    X509Certificate::from_der(self.leaf)
+                        .map(|(_, certificate)| BorrowedCertificate::X509(certificate))
+                        .map_err(X509Error::from)
+                }
+            })
+            .as_ref()
+            .map(Some)
+            .map_err(|e| e.clone().into())
+    }
+}
+
+/// Minimal reader for the TLS presentation language encoding used by RFC 6962.
+struct TlsReader<'a>(&'a [u8]);
+
+impl<'a> TlsReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CTLogError> {
+        if self.0.len() < n {
+            return Err(CTLogError::DecodeError("unexpected end of data".into()));
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, CTLogError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array_ref<const N: usize>(&mut self) -> Result<&'a [u8; N], CTLogError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], CTLogError> {
+        self.array_ref().copied()
+    }
+
+    /// Read an opaque vector with a `len_bytes` bytes length prefix.
+    fn opaque(&mut self, len_bytes: usize) -> Result<&'a [u8], CTLogError> {
+        let length = self
+            .take(len_bytes)?
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        self.take(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ct::v1::tests::crafted_entries;
+    use crate::ct::v1::{DecodedEntry, LogEntry};
+
+    /// The borrowed and owned decoders agree on `entries`.
+    fn assert_same(entries: &[Entry]) {
+        let batch = EntryBatch::decode(entries).unwrap();
+        for (entry, borrowed) in entries.iter().zip(batch.iter()) {
+            let borrowed = borrowed.unwrap();
+            let decoded = DecodedEntry::try_from(entry).unwrap();
+            assert_eq!(borrowed.version, decoded.version);
+            assert_eq!(borrowed.leaf_type, decoded.leaf_type);
+            assert_eq!(borrowed.timestamp, decoded.timestamp);
+            assert_eq!(borrowed.entry_type, decoded.entry_type());
+            assert_eq!(borrowed.extensions.is_some(), decoded.extensions.is_some());
+            assert_eq!(
+                borrowed.leaf_input,
+                base64::decode(&entry.leaf_input).unwrap()
+            );
+            assert_eq!(
+                borrowed.extra_data,
+                base64::decode(&entry.extra_data).unwrap()
+            );
+            assert_eq!(
+                borrowed.certificate().unwrap().is_some(),
+                decoded.info().is_some()
+            );
+            if let Some(chain) = decoded.chain() {
+                assert_eq!(borrowed.chain.len(), chain.certificates.len());
+            } else {
+                assert!(matches!(*decoded.entry, LogEntry::Unknown { .. }));
+                assert!(borrowed.chain.is_empty());
+                assert!(borrowed.leaf.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn matches_decoded_entry() {
+        assert_same(&crafted_entries(0, 0));
+        assert_same(&crafted_entries(0, 1));
+        assert_same(&crafted_entries(1, 1));
+        assert_same(&crafted_entries(11, 7));
+    }
+}
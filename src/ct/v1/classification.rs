@@ -0,0 +1,186 @@
+//! Validation level (DV, OV, IV or EV) of certificates.
+//!
+//! The level is read from the CertificatePolicies extension: the CA/Browser Forum policy
+//! identifiers of the
+//! [Baseline Requirements 7.1.6.1](https://cabforum.org/baseline-requirements-documents/),
+//! or one of the CA-specific EV policies of [EV_POLICY_OIDS]. Certificates without such a
+//! policy are classified from their subject attributes.
+
+use std::fmt;
+
+use oid_registry::{
+    Oid, MS_JURISDICTION_COUNTRY, OID_X509_BUSINESS_CATEGORY, OID_X509_GIVEN_NAME,
+    OID_X509_ORGANIZATION_NAME, OID_X509_SERIALNUMBER, OID_X509_SURNAME,
+};
+use serde::{Deserialize, Serialize};
+use x509_parser::prelude::*;
+
+use crate::ct::oid::{OID_CABF_DV, OID_CABF_EV, OID_CABF_IV, OID_CABF_OV};
+
+/// CA-specific EV policy identifiers predating (or used alongside) `2.23.140.1.1`, with the
+/// CA they belong to.
+pub const EV_POLICY_OIDS: &[(&str, &str)] = &[
+    ("1.2.392.200091.100.721.1", "SECOM"),
+    ("1.2.616.1.113527.2.5.1.1", "Certum"),
+    ("1.3.159.1.17.1", "Actalis"),
+    ("1.3.6.1.4.1.14370.1.6", "GeoTrust"),
+    ("1.3.6.1.4.1.14777.6.1.1", "Izenpe"),
+    ("1.3.6.1.4.1.4146.1.1", "GlobalSign"),
+    ("1.3.6.1.4.1.40869.1.1.22.3", "TWCA"),
+    ("1.3.6.1.4.1.4788.2.202.1", "D-TRUST"),
+    ("1.3.6.1.4.1.6334.1.100.1", "Cybertrust"),
+    ("1.3.6.1.4.1.6449.1.2.1.5.1", "Sectigo"),
+    ("1.3.6.1.4.1.7879.13.24.1", "T-Systems"),
+    ("1.3.6.1.4.1.782.1.2.1.8.1", "Network Solutions"),
+    ("1.3.6.1.4.1.8024.0.2.100.1.2", "QuoVadis"),
+    ("2.16.156.112554.3", "CFCA"),
+    ("2.16.578.1.26.1.3.3", "Buypass"),
+    ("2.16.756.1.89.1.2.1.1", "SwissSign"),
+    ("2.16.840.1.113733.1.7.23.6", "VeriSign"),
+    ("2.16.840.1.113733.1.7.48.1", "thawte"),
+    ("2.16.840.1.114028.10.1.2", "Entrust"),
+    ("2.16.840.1.114404.1.1.2.4.1", "Trustwave"),
+    ("2.16.840.1.114412.2.1", "DigiCert"),
+    ("2.16.840.1.114413.1.7.23.3", "Go Daddy"),
+    ("2.16.840.1.114414.1.7.23.3", "Starfield"),
+];
+
+/// How the identity of the subject was validated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ValidationLevel {
+    /// Domain Validated: control of the domain names only.
+    Dv,
+    /// Organization Validated.
+    Ov,
+    /// Individual Validated.
+    Iv,
+    /// Extended Validation.
+    Ev,
+}
+
+impl fmt::Display for ValidationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationLevel::Dv => write!(f, "DV"),
+            ValidationLevel::Ov => write!(f, "OV"),
+            ValidationLevel::Iv => write!(f, "IV"),
+            ValidationLevel::Ev => write!(f, "EV"),
+        }
+    }
+}
+
+/// Whether `oid` is an EV policy, of the CA/Browser Forum or of [EV_POLICY_OIDS].
+pub fn is_ev_policy(oid: &Oid) -> bool {
+    if *oid == OID_CABF_EV {
+        return true;
+    }
+    let oid = oid.to_id_string();
+    EV_POLICY_OIDS.iter().any(|(ev, _)| *ev == oid)
+}
+
+/// The validation level of a subscriber certificate. `None` for CA certificates.
+///
+/// The policies take precedence, EV first. Without a known policy, a certificate with the EV
+/// subject attributes (businessCategory, jurisdictionCountryName and serialNumber) is EV,
+/// one with an organizationName OV, one with a givenName or surname IV, and others DV.
+pub fn classify(certificate: &TbsCertificate) -> Option<ValidationLevel> {
+    if certificate.is_ca() {
+        return None;
+    }
+
+    let policies: Vec<&Oid> = certificate
+        .extensions()
+        .iter()
+        .filter_map(|extension| match extension.parsed_extension() {
+            ParsedExtension::CertificatePolicies(policies) => Some(policies),
+            _ => None,
+        })
+        .flat_map(|policies| policies.iter().map(|policy| &policy.policy_id))
+        .collect();
+    if policies.iter().any(|oid| is_ev_policy(oid)) {
+        return Some(ValidationLevel::Ev);
+    }
+    for (oid, level) in [
+        (OID_CABF_IV, ValidationLevel::Iv),
+        (OID_CABF_OV, ValidationLevel::Ov),
+        (OID_CABF_DV, ValidationLevel::Dv),
+    ] {
+        if policies.contains(&&oid) {
+            return Some(level);
+        }
+    }
+
+    let subject = certificate.subject();
+    let has = |oid: &Oid| subject.iter_by_oid(oid).next().is_some();
+    let level = if has(&OID_X509_BUSINESS_CATEGORY)
+        && has(&MS_JURISDICTION_COUNTRY)
+        && has(&OID_X509_SERIALNUMBER)
+    {
+        ValidationLevel::Ev
+    } else if has(&OID_X509_ORGANIZATION_NAME) {
+        ValidationLevel::Ov
+    } else if has(&OID_X509_GIVEN_NAME) || has(&OID_X509_SURNAME) {
+        ValidationLevel::Iv
+    } else {
+        ValidationLevel::Dv
+    };
+    Some(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ct::v1::tests::test_certificate;
+    use crate::utils::read_pem_certificates;
+
+    fn level(der: &[u8]) -> Option<ValidationLevel> {
+        let (_, certificate) = X509Certificate::from_der(der).unwrap();
+        classify(&certificate.tbs_certificate)
+    }
+
+    #[test]
+    fn ev_policies() {
+        assert!(is_ev_policy(&OID_CABF_EV));
+        assert!(!is_ev_policy(&OID_CABF_OV));
+        for (oid, _) in EV_POLICY_OIDS {
+            assert!(is_ev_policy(&oid.parse().unwrap()), "{oid}");
+        }
+        assert!(!is_ev_policy(&"2.16.840.1.114412.2.2".parse().unwrap()));
+    }
+
+    #[test]
+    fn levels() {
+        let pem = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/classification.pem"
+        ))
+        .unwrap();
+        let levels: Vec<_> = read_pem_certificates(&pem)
+            .unwrap()
+            .iter()
+            .map(|der| level(der))
+            .collect();
+        assert_eq!(
+            levels,
+            [
+                // EV, DV and OV policies
+                Some(ValidationLevel::Ev),
+                // DigiCert EV policy
+                Some(ValidationLevel::Ev),
+                // IV and OV policies
+                Some(ValidationLevel::Iv),
+                // businessCategory, jurisdictionCountryName, serialNumber and organizationName
+                Some(ValidationLevel::Ev),
+                Some(ValidationLevel::Ov),
+                Some(ValidationLevel::Iv),
+            ]
+        );
+
+        assert_eq!(
+            level(&test_certificate("final.pem")),
+            Some(ValidationLevel::Dv)
+        );
+        assert_eq!(level(&test_certificate("ca.pem")), None);
+    }
+}
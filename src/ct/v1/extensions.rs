@@ -0,0 +1,841 @@
+//! Typed decoding of the common X.509 extensions.
+//!
+//! [DecodedExtension] is a serializable form of the extensions found in WebPKI certificates,
+//! following [RFC 5280 4.2](https://datatracker.ietf.org/doc/html/rfc5280#section-4.2),
+//! [RFC 6962 3.1](https://datatracker.ietf.org/doc/html/rfc6962#section-3.1) for the CT
+//! extensions and [RFC 7633](https://datatracker.ietf.org/doc/html/rfc7633) for the TLS
+//! Feature. It is shown by the certificate `Display` implementations and included in the
+//! [ExtensionSummary](super::summary::ExtensionSummary).
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use oid_registry::{
+    format_oid, Oid, OidRegistry, OID_PKIX_ACCESS_DESCRIPTOR_CA_ISSUERS,
+    OID_PKIX_ACCESS_DESCRIPTOR_OCSP,
+};
+use serde::{Deserialize, Serialize};
+use x509_parser::objects::oid2abbrev;
+use x509_parser::prelude::*;
+
+use super::sct::{SignedCertificateTimestamp, SignedCertificateTimestampList};
+use super::summary::SubjectAltName;
+use crate::ct::oid::{self, OID_CT_LIST_SCT, OID_CT_PRECERT_POISON, OID_CT_PRECERT_SIGNING_CERT};
+use crate::utils::{encode_der_tlv, format_hex, format_ip_address, read_der_tlv};
+
+/// TLS Feature extension (1.3.6.1.5.5.7.1.24).
+pub const OID_PKIX_TLS_FEATURE: Oid<'static> = Oid::new(Cow::Borrowed(&[
+    0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x01, 0x18,
+]));
+
+/// CPS pointer policy qualifier (1.3.6.1.5.5.7.2.1).
+const OID_PKIX_QT_CPS: Oid<'static> = Oid::new(Cow::Borrowed(&[
+    0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x02, 0x01,
+]));
+
+/// User notice policy qualifier (1.3.6.1.5.5.7.2.2).
+const OID_PKIX_QT_UNOTICE: Oid<'static> = Oid::new(Cow::Borrowed(&[
+    0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x02, 0x02,
+]));
+
+/// Format a dotted OID with its short name, if known.
+fn format_dotted_oid(oid: &str, registry: &OidRegistry) -> String {
+    match Oid::from_str(oid) {
+        Ok(parsed) => format_oid(&parsed, registry),
+        Err(_) => oid.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessMethod {
+    Ocsp,
+    CaIssuers,
+    /// Dotted OID of another access method.
+    Other(String),
+}
+
+impl From<&Oid<'_>> for AccessMethod {
+    fn from(oid: &Oid) -> Self {
+        if *oid == OID_PKIX_ACCESS_DESCRIPTOR_OCSP {
+            Self::Ocsp
+        } else if *oid == OID_PKIX_ACCESS_DESCRIPTOR_CA_ISSUERS {
+            Self::CaIssuers
+        } else {
+            Self::Other(oid.to_id_string())
+        }
+    }
+}
+
+impl fmt::Display for AccessMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessMethod::Ocsp => write!(f, "OCSP"),
+            AccessMethod::CaIssuers => write!(f, "CA Issuers"),
+            AccessMethod::Other(method) => {
+                write!(f, "{}", format_dotted_oid(method, oid::registry()))
+            }
+        }
+    }
+}
+
+/// An AccessDescription of the AuthorityInfoAccess extension.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessDescription {
+    pub method: AccessMethod,
+    pub location: SubjectAltName,
+}
+
+impl From<&x509_parser::extensions::AccessDescription<'_>> for AccessDescription {
+    fn from(description: &x509_parser::extensions::AccessDescription) -> Self {
+        Self {
+            method: (&description.access_method).into(),
+            location: (&description.access_location).into(),
+        }
+    }
+}
+
+/// A DistributionPoint of the CRLDistributionPoints extension.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DistributionPoint {
+    /// The fullName of the distribution point.
+    pub full_name: Vec<SubjectAltName>,
+    /// The nameRelativeToCRLIssuer of the distribution point, e.g. `CN=CRL1`.
+    pub relative_name: Option<String>,
+    /// The reasons covered, as printed by OpenSSL. All reasons when absent.
+    pub reasons: Option<String>,
+    pub crl_issuer: Vec<SubjectAltName>,
+}
+
+impl From<&CRLDistributionPoint<'_>> for DistributionPoint {
+    fn from(point: &CRLDistributionPoint) -> Self {
+        let (full_name, relative_name) = match &point.distribution_point {
+            Some(DistributionPointName::FullName(names)) => {
+                (names.iter().map(Into::into).collect(), None)
+            }
+            Some(DistributionPointName::NameRelativeToCRLIssuer(rdn)) => {
+                let registry = oid::registry();
+                let components: Vec<_> = rdn
+                    .iter()
+                    .map(|attribute| {
+                        let value = attribute.as_str().map(|s| s.to_string());
+                        format!(
+                            "{}={}",
+                            oid2abbrev(attribute.attr_type(), registry)
+                                .map(|abbrev| abbrev.to_string())
+                                .unwrap_or_else(|_| attribute.attr_type().to_id_string()),
+                            value.unwrap_or_else(|_| "<invalid>".to_string())
+                        )
+                    })
+                    .collect();
+                (Vec::new(), Some(components.join("+")))
+            }
+            None => (Vec::new(), None),
+        };
+        Self {
+            full_name,
+            relative_name,
+            reasons: point.reasons.as_ref().map(|reasons| reasons.to_string()),
+            crl_issuer: point.crl_issuer.iter().flatten().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A PolicyQualifierInfo of the CertificatePolicies extension.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyQualifier {
+    /// URI of the Certification Practice Statement.
+    Cps(String),
+    UserNotice {
+        /// The organization of the noticeRef.
+        organization: Option<String>,
+        explicit_text: Option<String>,
+    },
+    /// Another qualifier: dotted OID and DER value, lowercase hex.
+    Other { oid: String, value: String },
+}
+
+/// Decode a DisplayText (or the IA5String of a CPS pointer).
+fn decode_display_text(tag: u8, content: &[u8]) -> Option<String> {
+    match tag {
+        // IA5String, VisibleString, UTF8String
+        0x16 | 0x1a | 0x0c => std::str::from_utf8(content).ok().map(|s| s.to_string()),
+        // BMPString, UCS-2 in 2-byte units
+        0x1e => {
+            let units = content.chunks_exact(2);
+            if !units.remainder().is_empty() {
+                return None;
+            }
+            let units: Vec<u16> = units
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                .collect();
+            String::from_utf16(&units).ok()
+        }
+        _ => None,
+    }
+}
+
+impl PolicyQualifier {
+    fn decode(oid: &Oid, qualifier: &[u8]) -> Option<Self> {
+        if *oid == OID_PKIX_QT_CPS {
+            let (tag, content, _) = read_der_tlv(qualifier)?;
+            return decode_display_text(tag, content).map(Self::Cps);
+        }
+        if *oid == OID_PKIX_QT_UNOTICE {
+            // UserNotice ::= SEQUENCE { noticeRef NoticeReference OPTIONAL,
+            //                           explicitText DisplayText OPTIONAL }
+            let (_, mut content, _) = read_der_tlv(qualifier)?;
+            let (mut organization, mut explicit_text) = (None, None);
+            while !content.is_empty() {
+                let (tag, value, rest) = read_der_tlv(content)?;
+                if tag == 0x30 {
+                    // NoticeReference ::= SEQUENCE { organization DisplayText, ... }
+                    let (tag, value, _) = read_der_tlv(value)?;
+                    organization = decode_display_text(tag, value);
+                } else {
+                    explicit_text = decode_display_text(tag, value);
+                }
+                content = rest;
+            }
+            return Some(Self::UserNotice {
+                organization,
+                explicit_text,
+            });
+        }
+        None
+    }
+}
+
+impl From<&PolicyQualifierInfo<'_>> for PolicyQualifier {
+    fn from(info: &PolicyQualifierInfo) -> Self {
+        Self::decode(&info.policy_qualifier_id, info.qualifier).unwrap_or_else(|| Self::Other {
+            oid: info.policy_qualifier_id.to_id_string(),
+            value: format_hex(info.qualifier),
+        })
+    }
+}
+
+/// A PolicyInformation of the CertificatePolicies extension.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Policy {
+    /// Dotted OID of the policy.
+    pub oid: String,
+    pub qualifiers: Vec<PolicyQualifier>,
+}
+
+impl From<&PolicyInformation<'_>> for Policy {
+    fn from(policy: &PolicyInformation) -> Self {
+        Self {
+            oid: policy.policy_id.to_id_string(),
+            qualifiers: policy
+                .policy_qualifiers
+                .iter()
+                .flatten()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyPurpose {
+    Any,
+    ServerAuth,
+    ClientAuth,
+    CodeSigning,
+    EmailProtection,
+    TimeStamping,
+    OcspSigning,
+    /// The Precertificate Signing Certificate purpose of RFC 6962.
+    PrecertSigning,
+    /// Dotted OID of another purpose.
+    Other(String),
+}
+
+impl KeyPurpose {
+    fn from_extended_key_usage(eku: &ExtendedKeyUsage) -> Vec<Self> {
+        let mut purposes: Vec<_> = [
+            (eku.any, Self::Any),
+            (eku.server_auth, Self::ServerAuth),
+            (eku.client_auth, Self::ClientAuth),
+            (eku.code_signing, Self::CodeSigning),
+            (eku.email_protection, Self::EmailProtection),
+            (eku.time_stamping, Self::TimeStamping),
+            (eku.ocsp_signing, Self::OcspSigning),
+        ]
+        .into_iter()
+        .filter_map(|(present, purpose)| present.then_some(purpose))
+        .collect();
+        purposes.extend(eku.other.iter().map(|oid| {
+            if *oid == OID_CT_PRECERT_SIGNING_CERT {
+                Self::PrecertSigning
+            } else {
+                Self::Other(oid.to_id_string())
+            }
+        }));
+        purposes
+    }
+}
+
+impl fmt::Display for KeyPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyPurpose::Any => write!(f, "Any Extended Key Usage"),
+            KeyPurpose::ServerAuth => write!(f, "TLS Web Server Authentication"),
+            KeyPurpose::ClientAuth => write!(f, "TLS Web Client Authentication"),
+            KeyPurpose::CodeSigning => write!(f, "Code Signing"),
+            KeyPurpose::EmailProtection => write!(f, "E-mail Protection"),
+            KeyPurpose::TimeStamping => write!(f, "Time Stamping"),
+            KeyPurpose::OcspSigning => write!(f, "OCSP Signing"),
+            KeyPurpose::PrecertSigning => write!(f, "CT Precertificate Signer"),
+            KeyPurpose::Other(purpose) => {
+                write!(f, "{}", format_dotted_oid(purpose, oid::registry()))
+            }
+        }
+    }
+}
+
+/// An SCT of the embedded SCT list extension.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddedSct {
+    /// The log ID, base64.
+    pub log_id: String,
+    /// The SCT timestamp, in milliseconds since the epoch.
+    pub timestamp: u64,
+    pub signature_algorithm: String,
+}
+
+impl From<&SignedCertificateTimestamp> for EmbeddedSct {
+    fn from(sct: &SignedCertificateTimestamp) -> Self {
+        Self {
+            log_id: sct.id.to_base64(),
+            timestamp: sct.timestamp,
+            signature_algorithm: sct.signature.algorithm_name(),
+        }
+    }
+}
+
+/// A name of the NameConstraints extension. iPAddress constraints are an address and a
+/// mask, shown as `address/mask`.
+fn subtree_name(subtree: &GeneralSubtree) -> SubjectAltName {
+    match subtree.base {
+        GeneralName::IPAddress(b) if b.len() == 8 || b.len() == 32 => {
+            let (address, mask) = b.split_at(b.len() / 2);
+            SubjectAltName::Ip(format!(
+                "{}/{}",
+                format_ip_address(address),
+                format_ip_address(mask)
+            ))
+        }
+        ref name => name.into(),
+    }
+}
+
+/// Decode the TLS Feature extension: `Features ::= SEQUENCE OF INTEGER`.
+fn decode_tls_features(value: &[u8]) -> Option<Vec<u16>> {
+    let (tag, mut content, _) = read_der_tlv(value)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let mut features = Vec::new();
+    while !content.is_empty() {
+        let (tag, integer, rest) = read_der_tlv(content)?;
+        if tag != 0x02 || integer.is_empty() || integer.len() > 3 {
+            return None;
+        }
+        let feature = integer.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32);
+        features.push(u16::try_from(feature).ok()?);
+        content = rest;
+    }
+    Some(features)
+}
+
+/// Name of a TLS extension of the TLS Feature extension.
+fn tls_feature_name(feature: u16) -> Option<&'static str> {
+    match feature {
+        5 => Some("status_request"),
+        17 => Some("status_request_v2"),
+        _ => None,
+    }
+}
+
+/// A decoded X.509 extension.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DecodedExtension {
+    AuthorityInfoAccess {
+        access: Vec<AccessDescription>,
+    },
+    CrlDistributionPoints {
+        points: Vec<DistributionPoint>,
+    },
+    CertificatePolicies {
+        policies: Vec<Policy>,
+    },
+    ExtendedKeyUsage {
+        purposes: Vec<KeyPurpose>,
+    },
+    NameConstraints {
+        permitted: Vec<SubjectAltName>,
+        excluded: Vec<SubjectAltName>,
+    },
+    PolicyConstraints {
+        require_explicit_policy: Option<u32>,
+        inhibit_policy_mapping: Option<u32>,
+    },
+    InhibitAnyPolicy {
+        skip_certs: u32,
+    },
+    /// The precertificate poison extension.
+    CtPrecertPoison,
+    /// The embedded SCT list extension.
+    CtSctList {
+        scts: Vec<EmbeddedSct>,
+    },
+    TlsFeature {
+        /// TLS extension numbers, e.g. 5 for `status_request` (OCSP Must-Staple).
+        features: Vec<u16>,
+    },
+}
+
+impl DecodedExtension {
+    /// Decode `extension`. `None` if it is not one of the supported extensions, or is
+    /// invalid.
+    pub fn decode(extension: &X509Extension) -> Option<Self> {
+        if extension.oid == OID_CT_PRECERT_POISON {
+            return Some(Self::CtPrecertPoison);
+        }
+        if extension.oid == OID_CT_LIST_SCT {
+            let list =
+                SignedCertificateTimestampList::from_extension_value(extension.value).ok()?;
+            return Some(Self::CtSctList {
+                scts: list.scts().map(Into::into).collect(),
+            });
+        }
+        if extension.oid == OID_PKIX_TLS_FEATURE {
+            return decode_tls_features(extension.value)
+                .map(|features| Self::TlsFeature { features });
+        }
+
+        match extension.parsed_extension() {
+            ParsedExtension::AuthorityInfoAccess(aia) => Some(Self::AuthorityInfoAccess {
+                access: aia.accessdescs.iter().map(Into::into).collect(),
+            }),
+            ParsedExtension::CRLDistributionPoints(points) => Some(Self::CrlDistributionPoints {
+                points: points.iter().map(Into::into).collect(),
+            }),
+            ParsedExtension::CertificatePolicies(policies) => Some(Self::CertificatePolicies {
+                policies: policies.iter().map(Into::into).collect(),
+            }),
+            ParsedExtension::ExtendedKeyUsage(eku) => Some(Self::ExtendedKeyUsage {
+                purposes: KeyPurpose::from_extended_key_usage(eku),
+            }),
+            ParsedExtension::NameConstraints(constraints) => Some(Self::NameConstraints {
+                permitted: constraints
+                    .permitted_subtrees
+                    .iter()
+                    .flatten()
+                    .map(subtree_name)
+                    .collect(),
+                excluded: constraints
+                    .excluded_subtrees
+                    .iter()
+                    .flatten()
+                    .map(subtree_name)
+                    .collect(),
+            }),
+            ParsedExtension::PolicyConstraints(constraints) => Some(Self::PolicyConstraints {
+                require_explicit_policy: constraints.require_explicit_policy,
+                inhibit_policy_mapping: constraints.inhibit_policy_mapping,
+            }),
+            ParsedExtension::InhibitAnyPolicy(inhibit) => Some(Self::InhibitAnyPolicy {
+                skip_certs: inhibit.skip_certs,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Decode the DER `value` of an extension identified by `oid`, e.g. from the hex value
+    /// of an [ExtensionSummary](super::summary::ExtensionSummary).
+    pub fn from_value(oid: &Oid, value: &[u8]) -> Option<Self> {
+        let mut content = encode_der_tlv(0x06, oid.as_bytes());
+        content.extend(encode_der_tlv(0x04, value));
+        let der = encode_der_tlv(0x30, &content);
+        let (_, extension) = X509Extension::from_der(&der).ok()?;
+        Self::decode(&extension)
+    }
+
+    /// The title of the extension, as printed by OpenSSL.
+    pub fn title(&self) -> &'static str {
+        match self {
+            DecodedExtension::AuthorityInfoAccess { .. } => "Authority Information Access",
+            DecodedExtension::CrlDistributionPoints { .. } => "X509v3 CRL Distribution Points",
+            DecodedExtension::CertificatePolicies { .. } => "X509v3 Certificate Policies",
+            DecodedExtension::ExtendedKeyUsage { .. } => "X509v3 Extended Key Usage",
+            DecodedExtension::NameConstraints { .. } => "X509v3 Name Constraints",
+            DecodedExtension::PolicyConstraints { .. } => "X509v3 Policy Constraints",
+            DecodedExtension::InhibitAnyPolicy { .. } => "X509v3 Inhibit Any Policy",
+            DecodedExtension::CtPrecertPoison => "CT Precertificate Poison",
+            DecodedExtension::CtSctList { .. } => "CT Precertificate SCTs",
+            DecodedExtension::TlsFeature { .. } => "TLS Feature",
+        }
+    }
+
+    pub fn display(&self) -> ExtensionDisplay<'_> {
+        ExtensionDisplay {
+            extension: self,
+            indent: 0,
+        }
+    }
+}
+
+/// Helper to [display](DecodedExtension::display) an extension.
+pub struct ExtensionDisplay<'a> {
+    extension: &'a DecodedExtension,
+    indent: usize,
+}
+
+impl<'a> ExtensionDisplay<'a> {
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+}
+
+impl fmt::Display for ExtensionDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indent = self.indent;
+        writeln!(f, "{:indent$}{}:", "", self.extension.title())?;
+        let indent = indent + 2;
+
+        match self.extension {
+            DecodedExtension::AuthorityInfoAccess { access } => {
+                for description in access {
+                    writeln!(
+                        f,
+                        "{:indent$}{} - {}",
+                        "", description.method, description.location
+                    )?;
+                }
+            }
+            DecodedExtension::CrlDistributionPoints { points } => {
+                for point in points {
+                    if !point.full_name.is_empty() {
+                        writeln!(f, "{:indent$}Full Name:", "")?;
+                        for name in &point.full_name {
+                            writeln!(f, "{:indent$}  {}", "", name)?;
+                        }
+                    }
+                    if let Some(name) = &point.relative_name {
+                        writeln!(f, "{:indent$}Relative Name:", "")?;
+                        writeln!(f, "{:indent$}  {}", "", name)?;
+                    }
+                    if let Some(reasons) = &point.reasons {
+                        writeln!(f, "{:indent$}Reasons: {}", "", reasons)?;
+                    }
+                    if !point.crl_issuer.is_empty() {
+                        writeln!(f, "{:indent$}CRL Issuer:", "")?;
+                        for name in &point.crl_issuer {
+                            writeln!(f, "{:indent$}  {}", "", name)?;
+                        }
+                    }
+                }
+            }
+            DecodedExtension::CertificatePolicies { policies } => {
+                let registry = oid::registry();
+                for policy in policies {
+                    writeln!(
+                        f,
+                        "{:indent$}Policy: {}",
+                        "",
+                        format_dotted_oid(&policy.oid, registry)
+                    )?;
+                    for qualifier in &policy.qualifiers {
+                        match qualifier {
+                            PolicyQualifier::Cps(uri) => {
+                                writeln!(f, "{:indent$}  CPS: {}", "", uri)?
+                            }
+                            PolicyQualifier::UserNotice {
+                                organization,
+                                explicit_text,
+                            } => {
+                                writeln!(f, "{:indent$}  User Notice:", "")?;
+                                if let Some(organization) = organization {
+                                    writeln!(
+                                        f,
+                                        "{:indent$}    Organization: {}",
+                                        "", organization
+                                    )?;
+                                }
+                                if let Some(text) = explicit_text {
+                                    writeln!(f, "{:indent$}    Explicit Text: {}", "", text)?;
+                                }
+                            }
+                            PolicyQualifier::Other { oid, value } => {
+                                writeln!(f, "{:indent$}  {}: {}", "", oid, value)?
+                            }
+                        }
+                    }
+                }
+            }
+            DecodedExtension::ExtendedKeyUsage { purposes } => {
+                let purposes: Vec<_> = purposes.iter().map(|p| p.to_string()).collect();
+                writeln!(f, "{:indent$}{}", "", purposes.join(", "))?;
+            }
+            DecodedExtension::NameConstraints {
+                permitted,
+                excluded,
+            } => {
+                for (title, names) in [("Permitted", permitted), ("Excluded", excluded)] {
+                    if !names.is_empty() {
+                        writeln!(f, "{:indent$}{}:", "", title)?;
+                        for name in names {
+                            writeln!(f, "{:indent$}  {}", "", name)?;
+                        }
+                    }
+                }
+            }
+            DecodedExtension::PolicyConstraints {
+                require_explicit_policy,
+                inhibit_policy_mapping,
+            } => {
+                if let Some(skip_certs) = require_explicit_policy {
+                    writeln!(f, "{:indent$}Require Explicit Policy:{}", "", skip_certs)?;
+                }
+                if let Some(skip_certs) = inhibit_policy_mapping {
+                    writeln!(f, "{:indent$}Inhibit Policy Mapping:{}", "", skip_certs)?;
+                }
+            }
+            DecodedExtension::InhibitAnyPolicy { skip_certs } => {
+                writeln!(f, "{:indent$}{}", "", skip_certs)?;
+            }
+            DecodedExtension::CtPrecertPoison => writeln!(f, "{:indent$}NULL", "")?,
+            DecodedExtension::CtSctList { scts } => {
+                for sct in scts {
+                    writeln!(
+                        f,
+                        "{:indent$}{} at {} ({})",
+                        "", sct.log_id, sct.timestamp, sct.signature_algorithm
+                    )?;
+                }
+            }
+            DecodedExtension::TlsFeature { features } => {
+                let features: Vec<_> = features
+                    .iter()
+                    .map(|feature| match tls_feature_name(*feature) {
+                        Some(name) => name.to_string(),
+                        None => feature.to_string(),
+                    })
+                    .collect();
+                writeln!(f, "{:indent$}{}", "", features.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oid_registry::{
+        OID_X509_EXT_CERTIFICATE_POLICIES, OID_X509_EXT_EXTENDED_KEY_USAGE,
+        OID_X509_EXT_NAME_CONSTRAINTS,
+    };
+
+    use super::*;
+
+    fn sequence(fields: &[Vec<u8>]) -> Vec<u8> {
+        encode_der_tlv(0x30, &fields.concat())
+    }
+
+    fn oid(dotted: &str) -> Vec<u8> {
+        encode_der_tlv(0x06, Oid::from_str(dotted).unwrap().as_bytes())
+    }
+
+    #[test]
+    fn tls_features() {
+        let features = |value: &[u8]| DecodedExtension::from_value(&OID_PKIX_TLS_FEATURE, value);
+
+        let must_staple = features(&[0x30, 0x03, 0x02, 0x01, 0x05]).unwrap();
+        assert_eq!(
+            must_staple,
+            DecodedExtension::TlsFeature { features: vec![5] }
+        );
+        assert_eq!(
+            features(&[0x30, 0x07, 0x02, 0x01, 0x05, 0x02, 0x02, 0x01, 0x00]),
+            Some(DecodedExtension::TlsFeature {
+                features: vec![5, 256]
+            })
+        );
+        assert_eq!(
+            must_staple.display().to_string(),
+            "TLS Feature:\n  status_request\n"
+        );
+
+        // Not a SEQUENCE, not an INTEGER, empty or out of range INTEGERs, truncated
+        assert_eq!(features(&[0x31, 0x03, 0x02, 0x01, 0x05]), None);
+        assert_eq!(features(&[0x30, 0x03, 0x04, 0x01, 0x05]), None);
+        assert_eq!(features(&[0x30, 0x02, 0x02, 0x00]), None);
+        assert_eq!(features(&[0x30, 0x05, 0x02, 0x03, 0x01, 0x00, 0x00]), None);
+        assert_eq!(features(&[0x30, 0x03, 0x02, 0x01]), None);
+        assert_eq!(features(&[]), None);
+    }
+
+    #[test]
+    fn display_text() {
+        assert_eq!(decode_display_text(0x0c, b"UTF8"), Some("UTF8".into()));
+        assert_eq!(decode_display_text(0x16, b"IA5"), Some("IA5".into()));
+        assert_eq!(decode_display_text(0x0c, &[0xff]), None);
+        assert_eq!(
+            decode_display_text(0x1e, &[0x00, 0x42, 0x00, 0x4d, 0x00, 0x50]),
+            Some("BMP".into())
+        );
+        assert_eq!(decode_display_text(0x1e, &[0x00, 0x42, 0x00]), None);
+        // An unpaired surrogate
+        assert_eq!(decode_display_text(0x1e, &[0xd8, 0x00]), None);
+        // PrintableString is not a DisplayText
+        assert_eq!(decode_display_text(0x13, b"Printable"), None);
+    }
+
+    #[test]
+    fn certificate_policies() {
+        let cps = sequence(&[
+            oid("1.3.6.1.5.5.7.2.1"),
+            encode_der_tlv(0x16, b"https://example.com/cps"),
+        ]);
+        let user_notice = sequence(&[
+            oid("1.3.6.1.5.5.7.2.2"),
+            sequence(&[
+                sequence(&[
+                    encode_der_tlv(0x0c, b"Example Org"),
+                    sequence(&[encode_der_tlv(0x02, &[1])]),
+                ]),
+                encode_der_tlv(0x1e, &[0x00, 0x48, 0x00, 0x69]),
+            ]),
+        ]);
+        let explicit_text = sequence(&[
+            oid("1.3.6.1.5.5.7.2.2"),
+            sequence(&[encode_der_tlv(0x1a, b"Visible")]),
+        ]);
+        let other = sequence(&[oid("1.2.3.4"), encode_der_tlv(0x05, &[])]);
+        let value = sequence(&[sequence(&[
+            oid("2.23.140.1.2.1"),
+            sequence(&[cps, user_notice, explicit_text, other]),
+        ])]);
+
+        assert_eq!(
+            DecodedExtension::from_value(&OID_X509_EXT_CERTIFICATE_POLICIES, &value),
+            Some(DecodedExtension::CertificatePolicies {
+                policies: vec![Policy {
+                    oid: "2.23.140.1.2.1".into(),
+                    qualifiers: vec![
+                        PolicyQualifier::Cps("https://example.com/cps".into()),
+                        PolicyQualifier::UserNotice {
+                            organization: Some("Example Org".into()),
+                            explicit_text: Some("Hi".into()),
+                        },
+                        PolicyQualifier::UserNotice {
+                            organization: None,
+                            explicit_text: Some("Visible".into()),
+                        },
+                        PolicyQualifier::Other {
+                            oid: "1.2.3.4".into(),
+                            value: "0500".into(),
+                        },
+                    ],
+                }],
+            })
+        );
+
+        // A truncated UserNotice is kept undecoded
+        let truncated = [0x30, 0x05, 0x0c, 0x04, b'a'];
+        assert_eq!(
+            PolicyQualifier::decode(&OID_PKIX_QT_UNOTICE, &truncated),
+            None
+        );
+        assert_eq!(
+            DecodedExtension::from_value(&OID_X509_EXT_CERTIFICATE_POLICIES, &value[..10]),
+            None
+        );
+    }
+
+    #[test]
+    fn name_constraints() {
+        let subtree = |tag, name: &[u8]| sequence(&[encode_der_tlv(tag, name)]);
+        let value = sequence(&[
+            encode_der_tlv(
+                0xa0,
+                &[
+                    subtree(0x82, b"example.com"),
+                    subtree(0x87, &[192, 168, 0, 0, 255, 255, 0, 0]),
+                ]
+                .concat(),
+            ),
+            encode_der_tlv(
+                0xa1,
+                &[
+                    subtree(
+                        0x87,
+                        &[
+                            &[0x20, 0x01, 0x0d, 0xb8][..],
+                            &[0; 12],
+                            &[0xff; 4],
+                            &[0; 12],
+                        ]
+                        .concat(),
+                    ),
+                    // An address without a mask
+                    subtree(0x87, &[10, 0, 0, 1]),
+                ]
+                .concat(),
+            ),
+        ]);
+
+        assert_eq!(
+            DecodedExtension::from_value(&OID_X509_EXT_NAME_CONSTRAINTS, &value),
+            Some(DecodedExtension::NameConstraints {
+                permitted: vec![
+                    SubjectAltName::Dns("example.com".into()),
+                    SubjectAltName::Ip("192.168.0.0/255.255.0.0".into()),
+                ],
+                excluded: vec![
+                    SubjectAltName::Ip("2001:db8::/ffff:ffff::".into()),
+                    SubjectAltName::Ip("10.0.0.1".into()),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn from_value() {
+        let poison = Oid::from_str("1.3.6.1.4.1.11129.2.4.3").unwrap();
+        assert_eq!(
+            DecodedExtension::from_value(&poison, &[0x05, 0x00]),
+            Some(DecodedExtension::CtPrecertPoison)
+        );
+
+        let eku = sequence(&[oid("1.3.6.1.5.5.7.3.1"), oid("1.3.6.1.4.1.11129.2.4.4")]);
+        assert_eq!(
+            DecodedExtension::from_value(&OID_X509_EXT_EXTENDED_KEY_USAGE, &eku),
+            Some(DecodedExtension::ExtendedKeyUsage {
+                purposes: vec![KeyPurpose::ServerAuth, KeyPurpose::PrecertSigning],
+            })
+        );
+        assert_eq!(
+            DecodedExtension::from_value(&OID_X509_EXT_EXTENDED_KEY_USAGE, &eku[..eku.len() - 1]),
+            None
+        );
+
+        let sct_list = Oid::from_str("1.3.6.1.4.1.11129.2.4.2").unwrap();
+        assert_eq!(
+            DecodedExtension::from_value(&sct_list, &[0x04, 0x02, 0x00, 0x10]),
+            None
+        );
+
+        // Unsupported extensions
+        let unknown = Oid::from_str("1.2.3.4").unwrap();
+        assert_eq!(DecodedExtension::from_value(&unknown, &[0x05, 0x00]), None);
+    }
+}
@@ -0,0 +1,248 @@
+//! Linking of precertificates with their final certificates.
+//!
+//! A precert entry and the entry of its final certificate log the same [PreCert]: the final
+//! certificate's TBSCertificate without the SCT list is the precertificate's without the
+//! poison, issued by the same key. [PrecertLinker] groups entries, possibly from several
+//! logs, by that [link_key], and reports final certificates without a precertificate and
+//! precertificates never finalized.
+
+use std::collections::HashMap;
+
+use ring::digest;
+
+use super::{DecodedEntry, LogEntry, PreCert};
+use crate::CTLogError;
+
+/// The hash of the issuer_key_hash and TBSCertificate of the [PreCert] an entry logs, or
+/// would log for a final certificate. `None` for X.509 entries without an issuer in their
+/// chain, and for unknown entries.
+pub fn link_key(entry: &DecodedEntry) -> Result<Option<[u8; 32]>, CTLogError> {
+    let hash = |pre_cert: &PreCert| {
+        let mut context = digest::Context::new(&digest::SHA256);
+        context.update(&pre_cert.issuer_key_hash.0);
+        context.update(pre_cert.tbs_certificate.as_der());
+        context.finish().as_ref().try_into().unwrap()
+    };
+
+    match &*entry.entry {
+        LogEntry::Precert { pre_cert, .. } => Ok(Some(hash(pre_cert))),
+        LogEntry::X509 { certificate, chain } => {
+            let issuer = match chain.certificates.first() {
+                Some(issuer) => issuer,
+                None => return Ok(None),
+            };
+            let pre_cert = PreCert::from_final_certificate(
+                certificate.certificate.borrow_certificate(),
+                issuer.certificate.borrow_certificate(),
+            )?;
+            Ok(Some(hash(&pre_cert)))
+        }
+        LogEntry::Unknown { .. } => Ok(None),
+    }
+}
+
+/// Where an entry was logged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedEntry {
+    /// The log, e.g. its URL.
+    pub log: String,
+    pub index: u64,
+    /// `None` for entries of an unknown version or leaf type.
+    pub timestamp: Option<u64>,
+}
+
+/// The precert and final certificate entries of one certificate.
+#[derive(Debug, Clone, Default)]
+pub struct CertificateLink {
+    pub subject: String,
+    /// Serial number, colon-separated hex.
+    pub serial: String,
+    pub precerts: Vec<LoggedEntry>,
+    pub finals: Vec<LoggedEntry>,
+}
+
+impl CertificateLink {
+    /// Whether both the precertificate and the final certificate were seen.
+    pub fn is_linked(&self) -> bool {
+        !self.precerts.is_empty() && !self.finals.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PrecertLinker {
+    links: HashMap<[u8; 32], CertificateLink>,
+    unlinkable: Vec<LoggedEntry>,
+}
+
+impl PrecertLinker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the entry at `index` of `log`.
+    pub fn add(&mut self, log: &str, index: u64, entry: &DecodedEntry) -> Result<(), CTLogError> {
+        let logged = LoggedEntry {
+            log: log.to_string(),
+            index,
+            timestamp: entry.timestamp,
+        };
+        let (key, certificate) = match (link_key(entry)?, entry.info()) {
+            (Some(key), Some(certificate)) => (key, certificate),
+            _ => {
+                self.unlinkable.push(logged);
+                return Ok(());
+            }
+        };
+
+        let link = self.links.entry(key).or_insert_with(|| CertificateLink {
+            subject: certificate.subject().to_string(),
+            serial: certificate.raw_serial_as_string(),
+            ..Default::default()
+        });
+        if entry.is_precert() {
+            link.precerts.push(logged);
+        } else {
+            link.finals.push(logged);
+        }
+        Ok(())
+    }
+
+    /// Add consecutive entries of `log`, the first one being at index `start`.
+    ///
+    /// Entries which fail to be added are skipped, and returned with their index.
+    pub fn add_all<'a>(
+        &mut self,
+        log: &str,
+        start: u64,
+        entries: impl IntoIterator<Item = &'a DecodedEntry>,
+    ) -> Vec<(u64, CTLogError)> {
+        (start..)
+            .zip(entries)
+            .filter_map(|(index, entry)| self.add(log, index, entry).err().map(|e| (index, e)))
+            .collect()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = &CertificateLink> {
+        self.links.values()
+    }
+
+    /// Certificates logged both as precertificate and final certificate.
+    pub fn linked(&self) -> impl Iterator<Item = &CertificateLink> {
+        self.links().filter(|link| link.is_linked())
+    }
+
+    /// Final certificates whose precertificate was not seen.
+    pub fn finals_without_precert(&self) -> impl Iterator<Item = &CertificateLink> {
+        self.links().filter(|link| link.precerts.is_empty())
+    }
+
+    /// Precertificates whose final certificate was not seen.
+    pub fn unfinalized_precerts(&self) -> impl Iterator<Item = &CertificateLink> {
+        self.links().filter(|link| link.finals.is_empty())
+    }
+
+    /// Entries which could not be linked: X.509 entries having no issuer in their chain, and
+    /// unknown entries.
+    pub fn unlinkable(&self) -> &[LoggedEntry] {
+        &self.unlinkable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use x509_parser::prelude::*;
+
+    use super::*;
+    use crate::ct::v1::tests::{crafted_entries, precert_entry, test_certificate, x509_entry};
+
+    fn certificates(names: &[&str]) -> Vec<Vec<u8>> {
+        names.iter().map(|name| test_certificate(name)).collect()
+    }
+
+    fn precert(names: &[&str]) -> DecodedEntry {
+        let chain = certificates(names);
+        let parsed: Vec<_> = chain
+            .iter()
+            .map(|der| X509Certificate::from_der(der).unwrap().1)
+            .collect();
+        let pre_cert = PreCert::from_precertificate(&parsed[0], &parsed[1], parsed.get(2)).unwrap();
+        precert_entry(pre_cert, &chain)
+    }
+
+    fn unknown() -> DecodedEntry {
+        DecodedEntry::try_from(&crafted_entries(0, 1)[0]).unwrap()
+    }
+
+    #[test]
+    fn precert_and_final_share_link_key() {
+        let precert_key = link_key(&precert(&["precert.pem", "ca.pem"])).unwrap();
+        let final_key = link_key(&x509_entry(&certificates(&["final.pem", "ca.pem"]))).unwrap();
+        assert!(precert_key.is_some());
+        assert_eq!(precert_key, final_key);
+
+        let other = link_key(&x509_entry(&certificates(&["lifetime_180d.pem", "ca.pem"])));
+        assert_ne!(other.unwrap(), precert_key);
+
+        assert_eq!(
+            link_key(&x509_entry(&certificates(&["final.pem"]))).unwrap(),
+            None
+        );
+        assert_eq!(link_key(&unknown()).unwrap(), None);
+        assert!(link_key(&x509_entry(&certificates(&[
+            "final_long_serial.pem",
+            "ca.pem"
+        ])))
+        .is_err());
+    }
+
+    #[test]
+    fn linker() {
+        let mut linker = PrecertLinker::new();
+        linker
+            .add("a", 0, &precert(&["precert.pem", "ca.pem"]))
+            .unwrap();
+        linker
+            .add("a", 1, &precert(&["psc_precert.pem", "psc.pem", "ca.pem"]))
+            .unwrap();
+        let failures = linker.add_all(
+            "b",
+            10,
+            &[
+                x509_entry(&certificates(&["final.pem", "ca.pem"])),
+                x509_entry(&certificates(&["final_long_serial.pem", "ca.pem"])),
+                x509_entry(&certificates(&["lifetime_180d.pem", "ca.pem"])),
+                x509_entry(&certificates(&["final.pem"])),
+                unknown(),
+            ],
+        );
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, 11);
+
+        let linked: Vec<_> = linker.linked().collect();
+        assert_eq!(linked.len(), 1);
+        assert_eq!(linked[0].subject, "CN=www.example.com");
+        assert_eq!(linked[0].serial, "42:42");
+        assert_eq!(
+            linked[0].precerts,
+            [LoggedEntry {
+                log: "a".into(),
+                index: 0,
+                timestamp: Some(1667260800000)
+            }]
+        );
+        assert_eq!(linked[0].finals[0].log, "b");
+        assert_eq!(linked[0].finals[0].index, 10);
+
+        let finals: Vec<_> = linker.finals_without_precert().collect();
+        assert_eq!(finals.len(), 1);
+        assert_eq!(finals[0].finals[0].index, 12);
+
+        let precerts: Vec<_> = linker.unfinalized_precerts().collect();
+        assert_eq!(precerts.len(), 1);
+        assert_eq!(precerts[0].precerts[0].index, 1);
+
+        let unlinkable: Vec<_> = linker.unlinkable().iter().map(|e| e.index).collect();
+        assert_eq!(unlinkable, [13, 14]);
+        assert_eq!(linker.unlinkable()[1].timestamp, None);
+    }
+}
@@ -0,0 +1,183 @@
+//! Merkle Tree hashes and proofs.
+//!
+//! [RFC 6962 2.1](https://datatracker.ietf.org/doc/html/rfc6962#section-2.1), with the
+//! verification algorithms of
+//! [RFC 9162 2.1.3.2](https://datatracker.ietf.org/doc/html/rfc9162#section-2.1.3.2).
+
+use ring::digest;
+
+/// The hash of a leaf: SHA-256(0x00 || leaf).
+pub fn leaf_hash(leaf: &[u8]) -> [u8; 32] {
+    let mut context = digest::Context::new(&digest::SHA256);
+    context.update(&[0x00]);
+    context.update(leaf);
+    context.finish().as_ref().try_into().unwrap()
+}
+
+/// The hash of an interior node: SHA-256(0x01 || left || right).
+pub fn node_hash(left: &[u8], right: &[u8]) -> [u8; 32] {
+    let mut context = digest::Context::new(&digest::SHA256);
+    context.update(&[0x01]);
+    context.update(left);
+    context.update(right);
+    context.finish().as_ref().try_into().unwrap()
+}
+
+/// Verify that `leaf_hash` is at `leaf_index` in the tree of `tree_size` leaves with
+/// `root_hash`, given the audit path of an inclusion proof.
+pub fn verify_inclusion(
+    leaf_hash: &[u8; 32],
+    leaf_index: u64,
+    tree_size: u64,
+    audit_path: &[[u8; 32]],
+    root_hash: &[u8; 32],
+) -> bool {
+    if leaf_index >= tree_size {
+        return false;
+    }
+
+    let mut f_n = leaf_index;
+    let mut s_n = tree_size - 1;
+    let mut r = *leaf_hash;
+    for p in audit_path {
+        if s_n == 0 {
+            return false;
+        }
+        if f_n & 1 == 1 || f_n == s_n {
+            r = node_hash(p, &r);
+            while f_n & 1 == 0 && f_n != 0 {
+                f_n >>= 1;
+                s_n >>= 1;
+            }
+        } else {
+            r = node_hash(&r, p);
+        }
+        f_n >>= 1;
+        s_n >>= 1;
+    }
+
+    s_n == 0 && &r == root_hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The leaves of the test tree of the Certificate Transparency reference implementations.
+    const LEAVES: [&str; 8] = [
+        "",
+        "00",
+        "10",
+        "2021",
+        "3031",
+        "40414243",
+        "5051525354555657",
+        "606162636465666768696a6b6c6d6e6f",
+    ];
+
+    fn decode_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn hash(hex: &str) -> [u8; 32] {
+        decode_hex(hex).try_into().unwrap()
+    }
+
+    fn leaf(index: usize) -> [u8; 32] {
+        leaf_hash(&decode_hex(LEAVES[index]))
+    }
+
+    fn root(tree_size: usize) -> [u8; 32] {
+        hash(
+            [
+                "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+                "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+                "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+                "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+                "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+                "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+                "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+                "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+            ][tree_size - 1],
+        )
+    }
+
+    fn proofs() -> Vec<(u64, u64, Vec<[u8; 32]>)> {
+        vec![
+            (0, 1, vec![]),
+            (
+                0,
+                8,
+                vec![
+                    hash("96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"),
+                    hash("5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e"),
+                    hash("6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4"),
+                ],
+            ),
+            (
+                5,
+                8,
+                vec![
+                    hash("bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b"),
+                    hash("ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0"),
+                    hash("d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7"),
+                ],
+            ),
+            (
+                2,
+                3,
+                vec![hash(
+                    "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+                )],
+            ),
+            (
+                1,
+                5,
+                vec![
+                    hash("6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"),
+                    hash("5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e"),
+                    hash("bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b"),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn inclusion_proofs() {
+        for (index, size, path) in proofs() {
+            let (leaf, root) = (leaf(index as usize), root(size as usize));
+            assert!(verify_inclusion(&leaf, index, size, &path, &root));
+
+            // Wrong leaf, index or root
+            assert!(!verify_inclusion(&[0; 32], index, size, &path, &root));
+            assert!(!verify_inclusion(&leaf, index, size, &path, &[0; 32]));
+            if size > 1 {
+                assert!(!verify_inclusion(&leaf, index ^ 1, size, &path, &root));
+            }
+        }
+    }
+
+    #[test]
+    fn inclusion_proof_length() {
+        for (index, size, path) in proofs() {
+            let (leaf, root) = (leaf(index as usize), root(size as usize));
+            if let Some((_, shorter)) = path.split_last() {
+                assert!(!verify_inclusion(&leaf, index, size, shorter, &root));
+            }
+            let mut longer = path.clone();
+            longer.push(root);
+            assert!(!verify_inclusion(&leaf, index, size, &longer, &root));
+        }
+    }
+
+    #[test]
+    fn inclusion_index_out_of_range() {
+        let path = &proofs()[1].2;
+        assert!(!verify_inclusion(&leaf(0), 8, 8, path, &root(8)));
+        assert!(!verify_inclusion(&leaf(0), 9, 8, path, &root(8)));
+        assert!(!verify_inclusion(&leaf(0), 0, 0, &[], &leaf(0)));
+    }
+}
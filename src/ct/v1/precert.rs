@@ -0,0 +1,251 @@
+//! Reconstruction of the [PreCert] logged for a certificate.
+//!
+//! [RFC 6962 3.2](https://datatracker.ietf.org/doc/html/rfc6962#section-3.2): the logged
+//! TBSCertificate is that of the final certificate without the SCT list extension (or, from
+//! the precertificate's side, without the poison extension), and `issuer_key_hash` is the
+//! hash of the final issuer's SubjectPublicKeyInfo.
+
+use oid_registry::{Oid, OID_X509_EXT_AUTHORITY_KEY_IDENTIFIER};
+use x509_parser::prelude::*;
+
+use super::{IssuerKeyHash, PreCert, WrapTbsCertificate};
+use crate::{
+    ct::oid::{OID_CT_LIST_SCT, OID_CT_PRECERT_POISON, OID_CT_PRECERT_SIGNING_CERT},
+    utils::{encode_der_tlv, read_der_tlv, sha256},
+    CTLogError,
+};
+
+/// Whether `certificate` is a Precertificate Signing Certificate.
+pub fn is_precert_signing_certificate(certificate: &TbsCertificate) -> bool {
+    match certificate.extended_key_usage() {
+        Ok(Some(eku)) => eku.value.other.contains(&OID_CT_PRECERT_SIGNING_CERT),
+        _ => false,
+    }
+}
+
+/// Whether `certificate` carries the critical poison extension of a precertificate.
+pub fn is_precertificate(certificate: &TbsCertificate) -> bool {
+    certificate
+        .extensions()
+        .iter()
+        .any(|extension| extension.oid == OID_CT_PRECERT_POISON && extension.critical)
+}
+
+impl PreCert {
+    fn new(issuer: &TbsCertificate, tbs: &[u8]) -> Result<Self, CTLogError> {
+        Ok(Self {
+            issuer_key_hash: IssuerKeyHash(sha256(issuer.public_key().raw)),
+            length: tbs.len() as u32,
+            tbs_certificate: Box::new(WrapTbsCertificate::from_bytes(tbs)?),
+        })
+    }
+
+    /// Rebuild the PreCert that was logged for a final `certificate` issued by `issuer`.
+    ///
+    /// `issuer` is always the CA that signed the final certificate; the TBSCertificate of a
+    /// final certificate already names it even if the precertificate was signed by a
+    /// Precertificate Signing Certificate.
+    pub fn from_final_certificate(
+        certificate: &X509Certificate,
+        issuer: &X509Certificate,
+    ) -> Result<Self, CTLogError> {
+        let tbs = rewrite_tbs(
+            certificate.tbs_certificate.as_ref(),
+            &[OID_CT_LIST_SCT],
+            None,
+        )?;
+        Self::new(issuer, &tbs)
+    }
+
+    /// Rebuild the PreCert that was logged for a `precertificate` signed by `issuer`.
+    ///
+    /// When `issuer` is a Precertificate Signing Certificate, `final_issuer` must be the CA
+    /// that issued it: the issuer name of the TBSCertificate is replaced by the final
+    /// issuer's, and its Authority Key Identifier by that of the Precertificate Signing
+    /// Certificate.
+    pub fn from_precertificate(
+        precertificate: &X509Certificate,
+        issuer: &X509Certificate,
+        final_issuer: Option<&X509Certificate>,
+    ) -> Result<Self, CTLogError> {
+        let tbs = precertificate.tbs_certificate.as_ref();
+
+        if !is_precert_signing_certificate(issuer) {
+            let tbs = rewrite_tbs(tbs, &[OID_CT_PRECERT_POISON], None)?;
+            return Self::new(issuer, &tbs);
+        }
+
+        let final_issuer = final_issuer.ok_or_else(|| {
+            CTLogError::DecodeError(
+                "precertificate signed by a Precertificate Signing Certificate needs the final issuer"
+                    .into(),
+            )
+        })?;
+        let authority_key_identifier = issuer
+            .extensions()
+            .iter()
+            .find(|extension| extension.oid == OID_X509_EXT_AUTHORITY_KEY_IDENTIFIER);
+        let tbs = rewrite_tbs(
+            tbs,
+            &[OID_CT_PRECERT_POISON],
+            Some(TbsReplacement {
+                issuer: final_issuer.subject().as_raw(),
+                authority_key_identifier: authority_key_identifier.map(|ext| ext.value),
+            }),
+        )?;
+        Self::new(final_issuer, &tbs)
+    }
+}
+
+/// Replacements applied to the TBSCertificate of a precertificate signed by a
+/// Precertificate Signing Certificate.
+pub(crate) struct TbsReplacement<'a> {
+    /// DER of the new issuer Name.
+    pub issuer: &'a [u8],
+    /// DER of the new extnValue of the Authority Key Identifier (kept if absent).
+    pub authority_key_identifier: Option<&'a [u8]>,
+}
+
+/// Re-encode a DER TBSCertificate without the extensions in `remove`, optionally
+/// replacing its issuer and Authority Key Identifier.
+pub(crate) fn rewrite_tbs(
+    tbs: &[u8],
+    remove: &[Oid],
+    replacement: Option<TbsReplacement>,
+) -> Result<Vec<u8>, CTLogError> {
+    let invalid = || CTLogError::DecodeError("invalid TBSCertificate".into());
+
+    let (tag, content, _) = read_der_tlv(tbs).ok_or_else(invalid)?;
+    if tag != 0x30 {
+        return Err(invalid());
+    }
+
+    let mut fields = Vec::new();
+    let mut rest = content;
+    // Position among the universal fields: serialNumber, signature, issuer, ...
+    let mut position = 0;
+    while !rest.is_empty() {
+        let (tag, field_content, next) = read_der_tlv(rest).ok_or_else(invalid)?;
+        let raw = &rest[..rest.len() - next.len()];
+        rest = next;
+
+        match tag {
+            // extensions [3] EXPLICIT Extensions
+            0xa3 => {
+                let (_, extensions, _) = read_der_tlv(field_content).ok_or_else(invalid)?;
+                let extensions = rewrite_extensions(extensions, remove, &replacement)?;
+                if !extensions.is_empty() {
+                    fields.push(encode_der_tlv(0xa3, &encode_der_tlv(0x30, &extensions)));
+                }
+            }
+            0xa0..=0xbf => fields.push(raw.to_vec()),
+            _ => {
+                match (&replacement, position) {
+                    (Some(replacement), 2) => fields.push(replacement.issuer.to_vec()),
+                    _ => fields.push(raw.to_vec()),
+                }
+                position += 1;
+            }
+        }
+    }
+
+    Ok(encode_der_tlv(0x30, &fields.concat()))
+}
+
+fn rewrite_extensions(
+    mut extensions: &[u8],
+    remove: &[Oid],
+    replacement: &Option<TbsReplacement>,
+) -> Result<Vec<u8>, CTLogError> {
+    let invalid = || CTLogError::DecodeError("invalid extensions".into());
+
+    let mut output = Vec::new();
+    while !extensions.is_empty() {
+        let (_, extension, next) = read_der_tlv(extensions).ok_or_else(invalid)?;
+        let raw = &extensions[..extensions.len() - next.len()];
+        extensions = next;
+
+        let (tag, oid, _) = read_der_tlv(extension).ok_or_else(invalid)?;
+        if tag != 0x06 {
+            return Err(invalid());
+        }
+        if remove.iter().any(|remove| remove.as_bytes() == oid) {
+            continue;
+        }
+
+        match replacement {
+            Some(TbsReplacement {
+                authority_key_identifier: Some(value),
+                ..
+            }) if oid == OID_X509_EXT_AUTHORITY_KEY_IDENTIFIER.as_bytes() => {
+                // Keep extnID and critical, replace extnValue
+                let mut fields = Vec::new();
+                let mut rest = extension;
+                while !rest.is_empty() {
+                    let (tag, _, next) = read_der_tlv(rest).ok_or_else(invalid)?;
+                    if tag == 0x04 {
+                        fields.push(encode_der_tlv(0x04, value));
+                    } else {
+                        fields.push(rest[..rest.len() - next.len()].to_vec());
+                    }
+                    rest = next;
+                }
+                output.extend(encode_der_tlv(0x30, &fields.concat()));
+            }
+            _ => output.extend_from_slice(raw),
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_pem_certificates;
+
+    fn pem(pem: &[u8]) -> Vec<u8> {
+        read_pem_certificates(pem).unwrap().remove(0)
+    }
+
+    #[test]
+    fn final_certificate_and_precertificate_agree() {
+        let ca = pem(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/ca.pem"
+        )));
+        let final_certificate = pem(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/final.pem"
+        )));
+        let precertificate = pem(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/precert.pem"
+        )));
+        let tbs = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/tbs.der"));
+
+        let (_, ca) = X509Certificate::from_der(&ca).unwrap();
+        let (_, final_certificate) = X509Certificate::from_der(&final_certificate).unwrap();
+        let (_, precertificate) = X509Certificate::from_der(&precertificate).unwrap();
+        assert!(is_precertificate(&precertificate));
+        assert!(!is_precertificate(&final_certificate));
+
+        let from_final = PreCert::from_final_certificate(&final_certificate, &ca).unwrap();
+        let from_precertificate = PreCert::from_precertificate(&precertificate, &ca, None).unwrap();
+        assert_eq!(from_final.tbs_certificate.as_der(), tbs);
+        assert_eq!(from_precertificate.tbs_certificate.as_der(), tbs);
+        assert_eq!(from_final.length as usize, tbs.len());
+        assert_eq!(from_final.issuer_key_hash.0, sha256(ca.public_key().raw));
+        assert_eq!(
+            from_precertificate.issuer_key_hash.0,
+            from_final.issuer_key_hash.0
+        );
+    }
+
+    #[test]
+    fn rewrite_tbs_keeps_other_extensions() {
+        let tbs = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/tbs.der"));
+        assert_eq!(rewrite_tbs(tbs, &[OID_CT_LIST_SCT], None).unwrap(), tbs);
+        assert!(rewrite_tbs(&tbs[..tbs.len() - 1], &[OID_CT_LIST_SCT], None).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! Output formats of certificates and log entries.
+//!
+//! [Render::render] displays a [WrapX509Certificate] or a [DecodedEntry] in the
+//! [OutputFormat] of its [RenderOptions]: the verbose `Display` layout, one compact line,
+//! PEM or JSON (the [summary](super::summary) model, one line per item).
+//!
+//! ```ignore
+//! let options = RenderOptions::new(OutputFormat::Compact).with_index(index);
+//! println!("{}", entry.render(options));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::SecondsFormat;
+
+use super::summary::{first_common_name, format_asn1_time, CertificateSummary, EntrySummary};
+use super::{CertificateInfo, DecodedEntry, WrapX509Certificate};
+use crate::utils::datetime_from_millis;
+use crate::CTLogError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// The `Display` layout, similar to `openssl x509 -text`.
+    #[default]
+    Verbose,
+    /// One line: index, timestamp, subject CN, issuer CN and notAfter.
+    Compact,
+    /// The certificate, followed by its chain for entries.
+    Pem,
+    /// The [EntrySummary] or [CertificateSummary], on one line.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = CTLogError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "verbose" | "text" => Ok(Self::Verbose),
+            "compact" => Ok(Self::Compact),
+            "pem" => Ok(Self::Pem),
+            "json" => Ok(Self::Json),
+            _ => Err(CTLogError::DecodeError(format!(
+                "unknown output format: {s}"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderOptions {
+    pub format: OutputFormat,
+    /// Index of the entry in its log, shown by the compact and JSON formats.
+    pub index: Option<u64>,
+}
+
+impl RenderOptions {
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            index: None,
+        }
+    }
+
+    pub fn with_index(mut self, index: u64) -> Self {
+        self.index = Some(index);
+        self
+    }
+}
+
+/// Types with several [OutputFormat]s. The verbose one is their `Display`.
+pub trait Render: fmt::Display + Sized {
+    fn fmt_compact(&self, f: &mut fmt::Formatter<'_>, options: &RenderOptions) -> fmt::Result;
+
+    fn fmt_pem(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    fn fmt_json(&self, f: &mut fmt::Formatter<'_>, options: &RenderOptions) -> fmt::Result;
+
+    fn render(&self, options: RenderOptions) -> Rendered<'_, Self> {
+        Rendered {
+            item: self,
+            options,
+        }
+    }
+}
+
+/// Helper to [render](Render::render) an item.
+pub struct Rendered<'a, T> {
+    item: &'a T,
+    options: RenderOptions,
+}
+
+impl<T: Render> fmt::Display for Rendered<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.options.format {
+            OutputFormat::Verbose => self.item.fmt(f),
+            OutputFormat::Compact => self.item.fmt_compact(f, &self.options),
+            OutputFormat::Pem => self.item.fmt_pem(f),
+            OutputFormat::Json => self.item.fmt_json(f, &self.options),
+        }
+    }
+}
+
+/// Subject CN, issuer CN and notAfter, `-` for a missing CN.
+fn fmt_compact_certificate(
+    f: &mut fmt::Formatter<'_>,
+    certificate: &(impl CertificateInfo + ?Sized),
+) -> fmt::Result {
+    writeln!(
+        f,
+        "{} {} {}",
+        first_common_name(certificate.subject())
+            .as_deref()
+            .unwrap_or("-"),
+        first_common_name(certificate.issuer())
+            .as_deref()
+            .unwrap_or("-"),
+        format_asn1_time(&certificate.validity().not_after)
+    )
+}
+
+fn write_json<T: serde::Serialize>(f: &mut fmt::Formatter<'_>, value: &T) -> fmt::Result {
+    let json = serde_json::to_string(value).map_err(|_| fmt::Error)?;
+    writeln!(f, "{}", json)
+}
+
+impl Render for WrapX509Certificate {
+    fn fmt_compact(&self, f: &mut fmt::Formatter<'_>, options: &RenderOptions) -> fmt::Result {
+        if let Some(index) = options.index {
+            write!(f, "{} ", index)?;
+        }
+        fmt_compact_certificate(f, self)
+    }
+
+    fn fmt_pem(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_pem())
+    }
+
+    fn fmt_json(&self, f: &mut fmt::Formatter<'_>, _options: &RenderOptions) -> fmt::Result {
+        write_json(f, &CertificateSummary::from(self))
+    }
+}
+
+impl Render for DecodedEntry {
+    fn fmt_compact(&self, f: &mut fmt::Formatter<'_>, options: &RenderOptions) -> fmt::Result {
+        match options.index {
+            Some(index) => write!(f, "{} ", index)?,
+            None => write!(f, "- ")?,
+        }
+        match self.timestamp {
+            Some(timestamp) => write!(
+                f,
+                "{} ",
+                datetime_from_millis(timestamp).map_or_else(
+                    || timestamp.to_string(),
+                    |time| time.to_rfc3339_opts(SecondsFormat::Millis, true)
+                )
+            )?,
+            None => write!(f, "- ")?,
+        }
+        match self.info() {
+            Some(certificate) => fmt_compact_certificate(f, certificate),
+            None => writeln!(f, "unknown entry"),
+        }
+    }
+
+    /// Unknown entries have no certificates and print nothing.
+    fn fmt_pem(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((certificate, chain)) = self.submitted_certificate().zip(self.chain()) {
+            write!(f, "{}{}", certificate.to_pem(), chain.to_pem())?;
+        }
+        Ok(())
+    }
+
+    fn fmt_json(&self, f: &mut fmt::Formatter<'_>, options: &RenderOptions) -> fmt::Result {
+        let mut summary = EntrySummary::from(self);
+        summary.index = options.index;
+        write_json(f, &summary)
+    }
+}
@@ -0,0 +1,140 @@
+//! Snapshots of the roots accepted by a log.
+//!
+//! A [RootsSnapshot] records the accepted roots returned by `get-roots` at some time, and can
+//! be saved as JSON. [RootsSnapshot::diff] reports the roots added and removed between two
+//! snapshots, e.g. to notice when a log stops accepting a CA.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::summary::format_asn1_time;
+use super::{CertificateInfo, WrapX509Certificate};
+use crate::{utils::format_hex, CTLogError};
+
+/// An accepted root, identified by its SHA-256 fingerprint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootSummary {
+    /// SHA-256 of the DER certificate, lowercase hex.
+    pub sha256: String,
+    pub subject: String,
+    /// notAfter, RFC 3339.
+    pub not_after: String,
+}
+
+impl From<&WrapX509Certificate> for RootSummary {
+    fn from(certificate: &WrapX509Certificate) -> Self {
+        Self {
+            sha256: format_hex(&certificate.sha256_fingerprint()),
+            subject: certificate.subject().to_string(),
+            not_after: format_asn1_time(&certificate.validity().not_after),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootsSnapshot {
+    /// URL of the log.
+    pub log: String,
+    pub taken_at: DateTime<Utc>,
+    pub roots: Vec<RootSummary>,
+}
+
+/// Roots added and removed between two [RootsSnapshot]s.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootsDiff {
+    pub added: Vec<RootSummary>,
+    pub removed: Vec<RootSummary>,
+}
+
+impl RootsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl RootsSnapshot {
+    pub fn new(log: &str, roots: &[WrapX509Certificate]) -> Self {
+        Self {
+            log: log.to_string(),
+            taken_at: Utc::now(),
+            roots: roots.iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Whether the root with this SHA-256 fingerprint is accepted.
+    pub fn contains(&self, sha256: &[u8; 32]) -> bool {
+        let sha256 = format_hex(sha256);
+        self.roots.iter().any(|root| root.sha256 == sha256)
+    }
+
+    /// The roots in `newer` but not in `self`, and in `self` but not in `newer`.
+    pub fn diff(&self, newer: &RootsSnapshot) -> RootsDiff {
+        let missing_from = |snapshot: &RootsSnapshot, roots: &[RootSummary]| {
+            roots
+                .iter()
+                .filter(|root| snapshot.roots.iter().all(|r| r.sha256 != root.sha256))
+                .cloned()
+                .collect()
+        };
+        RootsDiff {
+            added: missing_from(self, &newer.roots),
+            removed: missing_from(newer, &self.roots),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, CTLogError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CTLogError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, CTLogError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CTLogError> {
+        Ok(std::fs::write(path, self.to_json()?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ct::v1::tests::test_certificate;
+
+    fn root(name: &str) -> WrapX509Certificate {
+        WrapX509Certificate::from_der(&test_certificate(name)).unwrap()
+    }
+
+    #[test]
+    fn diff() {
+        let log = "https://ct.example.com/";
+        let older = RootsSnapshot::new(log, &[root("ca.pem"), root("v1_root.pem")]);
+        let newer = RootsSnapshot::new(log, &[root("v1_root.pem"), root("intermediate.pem")]);
+
+        let ca = root("ca.pem");
+        assert!(older.contains(&ca.sha256_fingerprint()));
+        assert!(!newer.contains(&ca.sha256_fingerprint()));
+        assert!(newer.contains(&root("v1_root.pem").sha256_fingerprint()));
+
+        let diff = older.diff(&newer);
+        assert_eq!(diff.removed, [RootSummary::from(&ca)]);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].subject, "CN=ctlog Test Intermediate");
+        assert_eq!(diff.added[0].not_after, "2027-10-31T00:00:00Z");
+        assert!(!diff.is_empty());
+        assert!(newer.diff(&newer).is_empty());
+    }
+
+    #[test]
+    fn json() {
+        let snapshot = RootsSnapshot::new("https://ct.example.com/", &[root("ca.pem")]);
+        let json = snapshot.to_json().unwrap();
+        assert_eq!(RootsSnapshot::from_json(&json).unwrap(), snapshot);
+        assert!(RootsSnapshot::from_json("{}").is_err());
+    }
+}
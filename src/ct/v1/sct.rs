@@ -0,0 +1,826 @@
+//! Signed Certificate Timestamps.
+//!
+//! [RFC 6962 3.2](https://datatracker.ietf.org/doc/html/rfc6962#section-3.2) and
+//! [3.3](https://datatracker.ietf.org/doc/html/rfc6962#section-3.3)
+
+use std::borrow::Cow;
+use std::fmt;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use deku::prelude::*;
+use oid_registry::{Oid, OID_KEY_TYPE_EC_PUBLIC_KEY, OID_PKCS1_RSAENCRYPTION};
+use ring::signature;
+use x509_parser::der_parser::der::parse_der_octetstring;
+use x509_parser::prelude::*;
+
+use super::{
+    precert::is_precertificate, AddChainResponse, CtExtensions, GetSthResponse, PreCert,
+    TimestampedEntrySignedInner, Version,
+};
+use crate::{
+    ct::oid::{OID_CT_LIST_SCT, OID_CT_OCSP_SCT},
+    loglist::LogList,
+    utils::{datetime_from_millis, encode_der_tlv, print_hex_rows, read_der_tlv},
+    CTLogError,
+};
+
+/// id-pkix-ocsp-basic (1.3.6.1.5.5.7.48.1.1).
+const OID_PKIX_OCSP_BASIC: Oid<'static> = Oid::new(Cow::Borrowed(&[
+    0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01,
+]));
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, DekuRead)]
+#[deku(type = "u8")]
+pub enum HashAlgorithm {
+    None = 0,
+    Md5 = 1,
+    Sha1 = 2,
+    Sha224 = 3,
+    Sha256 = 4,
+    Sha384 = 5,
+    Sha512 = 6,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, DekuRead)]
+#[deku(type = "u8")]
+pub enum SignatureAlgorithm {
+    Anonymous = 0,
+    Rsa = 1,
+    Dsa = 2,
+    Ecdsa = 3,
+}
+
+/// A TLS `digitally-signed` struct.
+///
+/// [RFC 5246 4.7](https://datatracker.ietf.org/doc/html/rfc5246#section-4.7)
+#[derive(Debug, Clone, DekuRead)]
+pub struct DigitallySigned {
+    pub hash_algorithm: HashAlgorithm,
+    pub signature_algorithm: SignatureAlgorithm,
+    #[deku(endian = "big")]
+    pub length: u16,
+    #[deku(count = "length")]
+    pub signature: Vec<u8>,
+}
+
+impl DigitallySigned {
+    /// TLS encoding of the struct.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut output = vec![self.hash_algorithm as u8, self.signature_algorithm as u8];
+        output.extend_from_slice(&(self.signature.len() as u16).to_be_bytes());
+        output.extend_from_slice(&self.signature);
+        output
+    }
+
+    /// Name of the algorithm, as printed by openssl.
+    pub fn algorithm_name(&self) -> String {
+        match (self.hash_algorithm, self.signature_algorithm) {
+            (HashAlgorithm::Sha256, SignatureAlgorithm::Ecdsa) => "ecdsa-with-SHA256".to_string(),
+            (HashAlgorithm::Sha256, SignatureAlgorithm::Rsa) => {
+                "sha256WithRSAEncryption".to_string()
+            }
+            (hash, signature) => format!("{:?}-with-{:?}", signature, hash),
+        }
+    }
+
+    /// Verify the signature over `data` with the log's DER SubjectPublicKeyInfo.
+    ///
+    /// Logs sign with ECDSA P-256 or RSA, both with SHA-256; anything else is an error.
+    pub fn verify(&self, data: &[u8], log_key: &[u8]) -> Result<bool, CTLogError> {
+        let (_, key) = SubjectPublicKeyInfo::from_der(log_key).map_err(X509Error::from)?;
+        let algorithm: &dyn signature::VerificationAlgorithm =
+            match (self.hash_algorithm, self.signature_algorithm) {
+                (HashAlgorithm::Sha256, SignatureAlgorithm::Ecdsa)
+                    if key.algorithm.algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY =>
+                {
+                    &signature::ECDSA_P256_SHA256_ASN1
+                }
+                (HashAlgorithm::Sha256, SignatureAlgorithm::Rsa)
+                    if key.algorithm.algorithm == OID_PKCS1_RSAENCRYPTION =>
+                {
+                    &signature::RSA_PKCS1_2048_8192_SHA256
+                }
+                _ => {
+                    return Err(CTLogError::DecodeError(format!(
+                        "unsupported signature {} for log key {}",
+                        self.algorithm_name(),
+                        key.algorithm.algorithm.to_id_string()
+                    )))
+                }
+            };
+
+        Ok(
+            signature::UnparsedPublicKey::new(algorithm, &key.subject_public_key.data)
+                .verify(data, &self.signature)
+                .is_ok(),
+        )
+    }
+}
+
+/// The SHA-256 hash of a log's public key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, DekuRead)]
+pub struct LogId(pub [u8; 32]);
+
+impl LogId {
+    pub fn to_base64(&self) -> String {
+        base64::encode(self.0)
+    }
+}
+
+impl fmt::LowerHex for LogId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, DekuRead)]
+pub struct SignedCertificateTimestamp {
+    pub sct_version: Version,
+    pub id: LogId,
+    #[deku(endian = "big")]
+    pub timestamp: u64,
+    pub extensions: CtExtensions,
+    pub signature: DigitallySigned,
+}
+
+impl TryFrom<&AddChainResponse> for SignedCertificateTimestamp {
+    type Error = CTLogError;
+
+    fn try_from(response: &AddChainResponse) -> Result<Self, CTLogError> {
+        let sct_version = match response.sct_version {
+            0 => Version::V1,
+            v => return Err(CTLogError::DecodeError(format!("unknown SCT version {v}"))),
+        };
+        let id = base64::decode(&response.id)?
+            .try_into()
+            .map_err(|_| CTLogError::DecodeError("log ID is not 32 bytes".into()))?;
+        let extensions = base64::decode(&response.extensions)?;
+        let signature = DigitallySigned::from_bytes((&base64::decode(&response.signature)?, 0))?.1;
+
+        Ok(Self {
+            sct_version,
+            id: LogId(id),
+            timestamp: response.timestamp,
+            extensions: CtExtensions {
+                length: extensions.len() as u16,
+                extensions,
+            },
+            signature,
+        })
+    }
+}
+
+impl SignedCertificateTimestamp {
+    /// The data signed by the log for `entry`. Fails for an unknown entry type.
+    ///
+    /// [RFC 6962 3.2](https://datatracker.ietf.org/doc/html/rfc6962#section-3.2)
+    pub fn signed_data(&self, entry: &TimestampedEntrySignedInner) -> Result<Vec<u8>, CTLogError> {
+        let mut data = vec![
+            self.sct_version.value(),
+            // SignatureType certificate_timestamp
+            0,
+        ];
+        data.extend_from_slice(&self.timestamp.to_be_bytes());
+        data.extend(entry.encode()?);
+        data.extend_from_slice(&self.extensions.length.to_be_bytes());
+        data.extend_from_slice(&self.extensions.extensions);
+        Ok(data)
+    }
+
+    /// TLS encoding of the SCT.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut output = vec![self.sct_version.value()];
+        output.extend_from_slice(&self.id.0);
+        output.extend_from_slice(&self.timestamp.to_be_bytes());
+        output.extend_from_slice(&(self.extensions.extensions.len() as u16).to_be_bytes());
+        output.extend_from_slice(&self.extensions.extensions);
+        output.extend(self.signature.encode());
+        output
+    }
+
+    /// The MerkleTreeLeaf the log adds for `entry` when it incorporates this SCT.
+    ///
+    /// [RFC 6962 3.4](https://datatracker.ietf.org/doc/html/rfc6962#section-3.4)
+    pub fn merkle_tree_leaf(
+        &self,
+        entry: &TimestampedEntrySignedInner,
+    ) -> Result<Vec<u8>, CTLogError> {
+        let mut leaf = self.signed_data(entry)?;
+        // Same layout as the signed data, with version v1 and leaf type timestamped_entry
+        leaf[0] = 0;
+        leaf[1] = 0;
+        Ok(leaf)
+    }
+
+    /// The Merkle leaf hash of [merkle_tree_leaf](Self::merkle_tree_leaf).
+    pub fn leaf_hash(&self, entry: &TimestampedEntrySignedInner) -> Result<[u8; 32], CTLogError> {
+        Ok(super::merkle::leaf_hash(&self.merkle_tree_leaf(entry)?))
+    }
+
+    /// Verify the signature over `entry` with the log's DER SubjectPublicKeyInfo.
+    pub fn verify_signature(
+        &self,
+        entry: &TimestampedEntrySignedInner,
+        log_key: &[u8],
+    ) -> Result<bool, CTLogError> {
+        self.signature.verify(&self.signed_data(entry)?, log_key)
+    }
+
+    /// Check the SCT for `entry` against the logs of `log_list`, at time `now`.
+    pub fn verify(
+        &self,
+        entry: &TimestampedEntrySignedInner,
+        log_list: &LogList,
+        now: DateTime<Utc>,
+    ) -> SctStatus {
+        let log = match log_list.find_by_id(&self.id.0) {
+            Some((_, log)) => log,
+            None => return SctStatus::UnknownLog,
+        };
+        let valid = log
+            .key_der()
+            .and_then(|key| self.verify_signature(entry, &key))
+            .unwrap_or(false);
+        if !valid {
+            SctStatus::InvalidSignature
+        } else if self.timestamp as i64 > now.timestamp_millis() {
+            SctStatus::FutureTimestamp
+        } else {
+            SctStatus::Valid
+        }
+    }
+
+    /// Display the SCT, naming its log when it is found in `log_list`.
+    pub fn display<'a>(&'a self, log_list: Option<&'a LogList>) -> SctDisplay<'a> {
+        SctDisplay {
+            sct: self,
+            log_list,
+            indent: 0,
+        }
+    }
+}
+
+/// Helper to [display](SignedCertificateTimestamp::display) an SCT.
+pub struct SctDisplay<'a> {
+    sct: &'a SignedCertificateTimestamp,
+    log_list: Option<&'a LogList>,
+    indent: usize,
+}
+
+impl<'a> SctDisplay<'a> {
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+}
+
+impl fmt::Display for SctDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sct = self.sct;
+        let indent = self.indent;
+        writeln!(f, "{:indent$}Signed Certificate Timestamp:", "")?;
+        let indent = indent + 2;
+        writeln!(f, "{:indent$}Version   : {:?}", "", sct.sct_version)?;
+        if let Some((operator, log)) = self.log_list.and_then(|list| list.find_by_id(&sct.id.0)) {
+            writeln!(
+                f,
+                "{:indent$}Log       : {} ({})",
+                "",
+                log.name(),
+                operator.name
+            )?;
+        }
+        writeln!(f, "{:indent$}Log ID    : {}", "", sct.id.to_base64())?;
+        writeln!(
+            f,
+            "{:indent$}Timestamp : {}",
+            "",
+            datetime_from_millis(sct.timestamp)
+                .map_or_else(|| sct.timestamp.to_string(), |time| time.to_string())
+        )?;
+        if sct.extensions.extensions.is_empty() {
+            writeln!(f, "{:indent$}Extensions: none", "")?;
+        } else {
+            writeln!(
+                f,
+                "{:indent$}Extensions: {}",
+                "",
+                base64::encode(&sct.extensions.extensions)
+            )?;
+        }
+        writeln!(
+            f,
+            "{:indent$}Signature : {}",
+            "",
+            sct.signature.algorithm_name()
+        )?;
+        print_hex_rows(f, &sct.signature.signature, indent + 12)
+    }
+}
+
+/// The outcome of [verifying](SignedCertificateTimestamp::verify) an SCT.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SctStatus {
+    Valid,
+    /// The log ID is not in the log list.
+    UnknownLog,
+    /// The signature does not match the entry, or the log key could not be used.
+    InvalidSignature,
+    /// Correctly signed, but issued after the verification time.
+    FutureTimestamp,
+}
+
+impl fmt::Display for SctStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SctStatus::Valid => write!(f, "valid"),
+            SctStatus::UnknownLog => write!(f, "unknown log"),
+            SctStatus::InvalidSignature => write!(f, "invalid signature"),
+            SctStatus::FutureTimestamp => write!(f, "timestamp in the future"),
+        }
+    }
+}
+
+/// Whether a log honoured an SCT, see [CTLogV1::audit_sct](crate::CTLogV1::audit_sct).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SctAuditStatus {
+    /// The entry is in the tree, with a verified inclusion proof.
+    Incorporated { leaf_index: u64, tree_size: u64 },
+    /// Not in the tree yet, but the latest STH is still within the MMD.
+    Pending {
+        /// SCT timestamp + MMD, in milliseconds.
+        deadline: u64,
+    },
+    /// Not in a tree whose STH is past the SCT timestamp + MMD.
+    MmdViolation {
+        deadline: u64,
+        sth_timestamp: u64,
+        tree_size: u64,
+    },
+}
+
+impl SctAuditStatus {
+    /// Classify an SCT issued at `sct_timestamp` against the latest `sth` of its log.
+    ///
+    /// `leaf_index` is the index of the verified inclusion proof of the entry, or `None` when
+    /// the log did not find the entry in the tree of `sth`.
+    pub fn classify(
+        sct_timestamp: u64,
+        mmd: Duration,
+        sth: &GetSthResponse,
+        leaf_index: Option<u64>,
+    ) -> Self {
+        if let Some(leaf_index) = leaf_index {
+            return SctAuditStatus::Incorporated {
+                leaf_index,
+                tree_size: sth.tree_size,
+            };
+        }
+        let deadline =
+            sct_timestamp.saturating_add(u64::try_from(mmd.as_millis()).unwrap_or(u64::MAX));
+        if sth.timestamp < deadline {
+            SctAuditStatus::Pending { deadline }
+        } else {
+            SctAuditStatus::MmdViolation {
+                deadline,
+                sth_timestamp: sth.timestamp,
+                tree_size: sth.tree_size,
+            }
+        }
+    }
+}
+
+impl fmt::Display for SctAuditStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SctAuditStatus::Incorporated {
+                leaf_index,
+                tree_size,
+            } => write!(
+                f,
+                "incorporated (leaf {} of tree size {})",
+                leaf_index, tree_size
+            ),
+            SctAuditStatus::Pending { .. } => write!(f, "pending (within MMD)"),
+            SctAuditStatus::MmdViolation { tree_size, .. } => {
+                write!(f, "MMD violation (not in tree size {})", tree_size)
+            }
+        }
+    }
+}
+
+/// An SCT together with its verification status.
+#[derive(Debug, Clone)]
+pub struct SctVerification {
+    pub sct: SignedCertificateTimestamp,
+    pub status: SctStatus,
+}
+
+/// Verify every SCT of `list`, issued for `entry`, against `log_list`.
+///
+/// `entry` is the [PreCert] for embedded SCTs, and the leaf [ASN1Cert](super::ASN1Cert)
+/// for SCTs delivered in the TLS extension or an OCSP response.
+pub fn verify_scts(
+    list: &SignedCertificateTimestampList,
+    entry: &TimestampedEntrySignedInner,
+    log_list: &LogList,
+) -> Vec<SctVerification> {
+    let now = Utc::now();
+    list.scts()
+        .map(|sct| SctVerification {
+            sct: sct.clone(),
+            status: sct.verify(entry, log_list, now),
+        })
+        .collect()
+}
+
+/// Verify the SCTs embedded in `certificate`, issued by `issuer`, against `log_list`.
+///
+/// Returns an empty list when the certificate has no SCT list extension.
+pub fn verify_embedded_scts(
+    certificate: &X509Certificate,
+    issuer: &X509Certificate,
+    log_list: &LogList,
+) -> Result<Vec<SctVerification>, CTLogError> {
+    if is_precertificate(certificate) {
+        return Err(CTLogError::DecodeError(
+            "precertificates do not embed SCTs".into(),
+        ));
+    }
+    let list = match SignedCertificateTimestampList::from_certificate(certificate)? {
+        Some(list) => list,
+        None => return Ok(Vec::new()),
+    };
+
+    let entry =
+        TimestampedEntrySignedInner::Precert(PreCert::from_final_certificate(certificate, issuer)?);
+    Ok(verify_scts(&list, &entry, log_list))
+}
+
+impl fmt::Display for SignedCertificateTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(None).fmt(f)
+    }
+}
+
+/// An SCT prefixed with its length.
+#[derive(Debug, Clone, DekuRead)]
+pub struct SerializedSct {
+    #[deku(endian = "big")]
+    pub length: u16,
+    #[deku(
+        count = "length",
+        map = "|v: &[u8]| -> Result<_, DekuError> { Ok(SignedCertificateTimestamp::from_bytes((v, 0))?.1) }"
+    )]
+    pub sct: SignedCertificateTimestamp,
+}
+
+#[derive(Debug, Clone, DekuRead)]
+pub struct SignedCertificateTimestampList {
+    #[deku(endian = "big")]
+    pub length: u16,
+    #[deku(bytes_read = "length")]
+    pub sct_list: Vec<SerializedSct>,
+}
+
+impl SignedCertificateTimestampList {
+    pub fn from_scts(scts: Vec<SignedCertificateTimestamp>) -> Self {
+        let sct_list: Vec<_> = scts
+            .into_iter()
+            .map(|sct| SerializedSct {
+                length: sct.encode().len() as u16,
+                sct,
+            })
+            .collect();
+        Self {
+            length: sct_list.iter().map(|sct| 2 + sct.length).sum(),
+            sct_list,
+        }
+    }
+
+    /// TLS encoding of the list, as sent in the TLS `signed_certificate_timestamp` extension.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut list = Vec::new();
+        for serialized in &self.sct_list {
+            let sct = serialized.sct.encode();
+            list.extend_from_slice(&(sct.len() as u16).to_be_bytes());
+            list.extend(sct);
+        }
+        let mut output = (list.len() as u16).to_be_bytes().to_vec();
+        output.extend(list);
+        output
+    }
+
+    /// DER value of the SCT list X.509 extension, for embedding in a certificate.
+    pub fn to_extension_value(&self) -> Vec<u8> {
+        encode_der_tlv(0x04, &self.encode())
+    }
+
+    /// Parse the TLS encoding of a SignedCertificateTimestampList.
+    pub fn parse(bytes: &[u8]) -> Result<Self, CTLogError> {
+        Ok(Self::from_bytes((bytes, 0))?.1)
+    }
+
+    /// Parse the `extension_data` of the TLS `signed_certificate_timestamp` extension.
+    pub fn from_tls_extension(extension_data: &[u8]) -> Result<Self, CTLogError> {
+        let ((rest, _), list) = Self::from_bytes((extension_data, 0))?;
+        if !rest.is_empty() {
+            return Err(CTLogError::DecodeError(
+                "trailing bytes after SCT list".into(),
+            ));
+        }
+        Ok(list)
+    }
+
+    /// Parse the value of the SCT list X.509 extension (1.3.6.1.4.1.11129.2.4.2),
+    /// an OCTET STRING wrapping the TLS encoding.
+    pub fn from_extension_value(value: &[u8]) -> Result<Self, CTLogError> {
+        let (_, octets) = parse_der_octetstring(value).map_err(X509Error::from)?;
+        Self::parse(octets.as_slice().map_err(X509Error::from)?)
+    }
+
+    /// Find and parse the SCT list extension of a certificate.
+    pub fn from_certificate(certificate: &TbsCertificate) -> Result<Option<Self>, CTLogError> {
+        certificate
+            .extensions()
+            .iter()
+            .find(|extension| extension.oid == OID_CT_LIST_SCT)
+            .map(|extension| Self::from_extension_value(extension.value))
+            .transpose()
+    }
+
+    /// Find and parse the SCT list extension of a DER OCSPResponse.
+    ///
+    /// The extension is looked up in the singleExtensions of each SingleResponse, the
+    /// first one found is returned. Unsuccessful responses have no SCTs.
+    pub fn from_ocsp_response(der: &[u8]) -> Result<Option<Self>, CTLogError> {
+        let invalid = || CTLogError::DecodeError("invalid OCSP response".into());
+        let expect = |input, tag| match read_der_tlv(input) {
+            Some((t, content, rest)) if t == tag => Ok((content, rest)),
+            _ => Err(invalid()),
+        };
+
+        // OCSPResponse ::= SEQUENCE { responseStatus, responseBytes [0] EXPLICIT OPTIONAL }
+        let (response, _) = expect(der, 0x30)?;
+        let (status, rest) = expect(response, 0x0a)?;
+        if status != [0] || rest.is_empty() {
+            return Ok(None);
+        }
+        let (response_bytes, _) = expect(rest, 0xa0)?;
+        let (response_bytes, _) = expect(response_bytes, 0x30)?;
+        let (response_type, rest) = expect(response_bytes, 0x06)?;
+        if response_type != OID_PKIX_OCSP_BASIC.as_bytes() {
+            return Err(CTLogError::DecodeError(
+                "unsupported OCSP response type".into(),
+            ));
+        }
+        let (basic, _) = expect(rest, 0x04)?;
+
+        // BasicOCSPResponse ::= SEQUENCE { tbsResponseData, ... }
+        let (basic, _) = expect(basic, 0x30)?;
+        let (mut response_data, _) = expect(basic, 0x30)?;
+
+        // ResponseData ::= SEQUENCE { version [0], responderID, producedAt, responses, ... }
+        let mut responses = None;
+        while !response_data.is_empty() {
+            let (tag, content, rest) = read_der_tlv(response_data).ok_or_else(invalid)?;
+            if tag == 0x30 {
+                responses = Some(content);
+                break;
+            }
+            response_data = rest;
+        }
+        let mut responses = responses.ok_or_else(invalid)?;
+
+        while !responses.is_empty() {
+            let (single, rest) = expect(responses, 0x30)?;
+            responses = rest;
+
+            // SingleResponse ::= SEQUENCE { certID, certStatus, thisUpdate,
+            //   nextUpdate [0] OPTIONAL, singleExtensions [1] OPTIONAL }
+            let (_, single) = expect(single, 0x30)?;
+            // CertStatus ::= CHOICE { good [0] IMPLICIT NULL,
+            //   revoked [1] IMPLICIT RevokedInfo, unknown [2] IMPLICIT NULL }
+            let single = match read_der_tlv(single) {
+                Some((0x80 | 0xa1 | 0x82, _, rest)) => rest,
+                _ => return Err(invalid()),
+            };
+            let (_, mut single) = expect(single, 0x18)?;
+            if let Some((0xa0, _, rest)) = read_der_tlv(single) {
+                single = rest;
+            }
+            if single.is_empty() {
+                continue;
+            }
+            let (extensions, _) = expect(single, 0xa1)?;
+            let (mut extensions, _) = expect(extensions, 0x30)?;
+            while !extensions.is_empty() {
+                let (extension, rest) = expect(extensions, 0x30)?;
+                extensions = rest;
+                let (oid, mut fields) = expect(extension, 0x06)?;
+                if oid != OID_CT_OCSP_SCT.as_bytes() {
+                    continue;
+                }
+                // Skip the critical BOOLEAN, the value is the last field
+                let mut value = &[][..];
+                while !fields.is_empty() {
+                    let (tag, content, rest) = read_der_tlv(fields).ok_or_else(invalid)?;
+                    if tag == 0x04 {
+                        value = content;
+                    }
+                    fields = rest;
+                }
+                return Self::from_extension_value(value).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn scts(&self) -> impl Iterator<Item = &SignedCertificateTimestamp> {
+        self.sct_list.iter().map(|serialized| &serialized.sct)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ct::v1::LogEntryType;
+    use crate::utils::read_pem_certificates;
+
+    const LOG_LIST: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/data/loglist.json"
+    ));
+    const LOG_KEY: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/data/log_key.der"
+    ));
+    const SIGNED_DATA: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/data/sct_signed_data.bin"
+    ));
+
+    fn pem(pem: &[u8]) -> Vec<u8> {
+        read_pem_certificates(pem).unwrap().remove(0)
+    }
+
+    fn certificates() -> (Vec<u8>, Vec<u8>) {
+        (
+            pem(include_bytes!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/data/final.pem"
+            ))),
+            pem(include_bytes!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/data/ca.pem"
+            ))),
+        )
+    }
+
+    #[test]
+    fn embedded_sct_signature() {
+        let (certificate, issuer) = certificates();
+        let (_, certificate) = X509Certificate::from_der(&certificate).unwrap();
+        let (_, issuer) = X509Certificate::from_der(&issuer).unwrap();
+
+        let list = SignedCertificateTimestampList::from_certificate(&certificate)
+            .unwrap()
+            .unwrap();
+        let sct = list.scts().next().unwrap();
+        let entry = TimestampedEntrySignedInner::Precert(
+            PreCert::from_final_certificate(&certificate, &issuer).unwrap(),
+        );
+        assert_eq!(sct.signed_data(&entry).unwrap(), SIGNED_DATA);
+        assert!(sct.verify_signature(&entry, LOG_KEY).unwrap());
+
+        let mut tampered = sct.clone();
+        tampered.timestamp += 1;
+        assert!(!tampered.verify_signature(&entry, LOG_KEY).unwrap());
+
+        let unknown = TimestampedEntrySignedInner::Unknown(LogEntryType::Unknown(7));
+        assert!(sct.signed_data(&unknown).is_err());
+        assert!(sct.leaf_hash(&unknown).is_err());
+        assert!(sct.verify_signature(&unknown, LOG_KEY).is_err());
+
+        let mut rsa = sct.clone();
+        rsa.signature.signature_algorithm = SignatureAlgorithm::Rsa;
+        assert!(rsa.verify_signature(&entry, LOG_KEY).is_err());
+    }
+
+    #[test]
+    fn embedded_scts_against_log_list() {
+        let (certificate, issuer) = certificates();
+        let (_, certificate) = X509Certificate::from_der(&certificate).unwrap();
+        let (_, issuer) = X509Certificate::from_der(&issuer).unwrap();
+        let log_list = LogList::from_json(LOG_LIST).unwrap();
+
+        let verifications = verify_embedded_scts(&certificate, &issuer, &log_list).unwrap();
+        assert_eq!(verifications.len(), 1);
+        assert!(matches!(verifications[0].status, SctStatus::Valid));
+
+        // The SCT was issued for the certificate's own issuer, not for itself
+        let verifications = verify_embedded_scts(&certificate, &certificate, &log_list).unwrap();
+        assert!(matches!(
+            verifications[0].status,
+            SctStatus::InvalidSignature
+        ));
+
+        let log_id = &log_list.logs().next().unwrap().1.log_id;
+        let empty =
+            LogList::from_json(&LOG_LIST.replace(log_id, &base64::encode([0; 32]))).unwrap();
+        let verifications = verify_embedded_scts(&certificate, &issuer, &empty).unwrap();
+        assert!(matches!(verifications[0].status, SctStatus::UnknownLog));
+    }
+
+    #[test]
+    fn ocsp_response() {
+        let ocsp = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/ocsp.der"));
+        let (certificate, _) = certificates();
+        let (_, certificate) = X509Certificate::from_der(&certificate).unwrap();
+        let embedded = SignedCertificateTimestampList::from_certificate(&certificate)
+            .unwrap()
+            .unwrap();
+
+        let list = SignedCertificateTimestampList::from_ocsp_response(ocsp)
+            .unwrap()
+            .unwrap();
+        assert_eq!(list.encode(), embedded.encode());
+
+        // certStatus revoked shares the [1] tag of singleExtensions
+        let revoked = include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/ocsp_revoked.der"
+        ));
+        let list = SignedCertificateTimestampList::from_ocsp_response(revoked)
+            .unwrap()
+            .unwrap();
+        assert_eq!(list.encode(), embedded.encode());
+
+        for length in [0, 1, 10, ocsp.len() / 2, ocsp.len() - 1] {
+            assert!(SignedCertificateTimestampList::from_ocsp_response(&ocsp[..length]).is_err());
+        }
+
+        // tryLater, without responseBytes
+        let unsuccessful = [0x30, 0x03, 0x0a, 0x01, 0x03];
+        assert!(
+            SignedCertificateTimestampList::from_ocsp_response(&unsuccessful)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn audit_status() {
+        let sth = GetSthResponse {
+            tree_size: 8,
+            timestamp: 1_000_000,
+            sha256_root_hash: String::new(),
+            tree_head_signature: String::new(),
+        };
+        let mmd = Duration::from_secs(60);
+
+        assert_eq!(
+            SctAuditStatus::classify(999_000, mmd, &sth, Some(3)),
+            SctAuditStatus::Incorporated {
+                leaf_index: 3,
+                tree_size: 8
+            }
+        );
+        // Incorporated even past the MMD
+        assert_eq!(
+            SctAuditStatus::classify(0, mmd, &sth, Some(3)),
+            SctAuditStatus::Incorporated {
+                leaf_index: 3,
+                tree_size: 8
+            }
+        );
+        assert_eq!(
+            SctAuditStatus::classify(940_001, mmd, &sth, None),
+            SctAuditStatus::Pending {
+                deadline: 1_000_001
+            }
+        );
+        // The STH at the deadline was due to include the entry
+        assert_eq!(
+            SctAuditStatus::classify(940_000, mmd, &sth, None),
+            SctAuditStatus::MmdViolation {
+                deadline: 1_000_000,
+                sth_timestamp: 1_000_000,
+                tree_size: 8
+            }
+        );
+        assert_eq!(
+            SctAuditStatus::classify(u64::MAX - 1, mmd, &sth, None),
+            SctAuditStatus::Pending { deadline: u64::MAX }
+        );
+        assert_eq!(
+            SctAuditStatus::classify(1, Duration::MAX, &sth, None),
+            SctAuditStatus::Pending { deadline: u64::MAX }
+        );
+    }
+}
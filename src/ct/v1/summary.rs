@@ -0,0 +1,263 @@
+//! A stable, serializable summary of a [DecodedEntry].
+//!
+//! [EntrySummary] is the schema our tools emit as JSON. Fields are only ever added;
+//! a breaking change bumps [ENTRY_SUMMARY_VERSION].
+
+use std::fmt;
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use x509_parser::prelude::*;
+
+use super::classification::ValidationLevel;
+use super::extensions::DecodedExtension;
+use super::{CertificateInfo, DecodedEntry, LogEntry, LogEntryType, WrapX509Certificate};
+use crate::ct::oid;
+use crate::utils::{datetime_from_millis, format_hex, format_ip_address};
+
+/// Version of the [EntrySummary] schema.
+///
+/// Version 2 made `entry_type`, `timestamp` and `logged_at` nullable and the certificate
+/// fields optional, for entries of an unknown version, leaf type or entry type.
+pub const ENTRY_SUMMARY_VERSION: u32 = 2;
+
+/// A subjectAltName entry, typed by kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum SubjectAltName {
+    Dns(String),
+    Ip(String),
+    Email(String),
+    Uri(String),
+    DirectoryName(String),
+    RegisteredId(String),
+    /// otherName, x400Address and ediPartyName, in debug form.
+    Other(String),
+}
+
+impl From<&GeneralName<'_>> for SubjectAltName {
+    fn from(name: &GeneralName) -> Self {
+        match name {
+            GeneralName::DNSName(s) => Self::Dns(s.to_string()),
+            GeneralName::IPAddress(b) => Self::Ip(format_ip_address(b)),
+            GeneralName::RFC822Name(s) => Self::Email(s.to_string()),
+            GeneralName::URI(s) => Self::Uri(s.to_string()),
+            GeneralName::DirectoryName(n) => Self::DirectoryName(n.to_string()),
+            GeneralName::RegisteredID(oid) => Self::RegisteredId(oid.to_id_string()),
+            x => Self::Other(format!("{:?}", x)),
+        }
+    }
+}
+
+impl fmt::Display for SubjectAltName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubjectAltName::Dns(s) => write!(f, "DNS:{}", s),
+            SubjectAltName::Ip(s) => write!(f, "IP Address:{}", s),
+            SubjectAltName::Email(s) => write!(f, "email:{}", s),
+            SubjectAltName::Uri(s) => write!(f, "URI:{}", s),
+            SubjectAltName::DirectoryName(s) => write!(f, "DirName:{}", s),
+            SubjectAltName::RegisteredId(s) => write!(f, "Registered ID:{}", s),
+            SubjectAltName::Other(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Fingerprints and identifiers of the logged certificate (the precertificate for precert
+/// entries).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprints {
+    /// SHA-1 of the DER certificate, lowercase hex.
+    pub sha1: String,
+    /// SHA-256 of the DER certificate, lowercase hex.
+    pub sha256: String,
+    /// SHA-256 of the DER SubjectPublicKeyInfo, base64 (the `pin-sha256` format).
+    #[serde(default)]
+    pub spki_sha256: String,
+    /// The subjectKeyIdentifier, lowercase hex.
+    #[serde(default)]
+    pub subject_key_identifier: Option<String>,
+    /// SHA-256 of the DER TBSCertificate, lowercase hex.
+    #[serde(default)]
+    pub tbs_sha256: String,
+}
+
+impl Fingerprints {
+    pub fn from_certificate(certificate: &WrapX509Certificate) -> Self {
+        Self {
+            sha1: format_hex(&certificate.sha1_fingerprint()),
+            sha256: format_hex(&certificate.sha256_fingerprint()),
+            spki_sha256: base64::encode(certificate.spki_sha256()),
+            subject_key_identifier: certificate.subject_key_identifier().map(format_hex),
+            tbs_sha256: format_hex(&certificate.tbs_sha256()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtensionSummary {
+    /// Dotted OID of the extension.
+    pub oid: String,
+    /// Short name of the extension, if known.
+    pub name: Option<String>,
+    pub critical: bool,
+    /// DER-encoded extension value, lowercase hex.
+    pub value: String,
+    /// The decoded value, for the extensions [DecodedExtension] supports.
+    #[serde(default)]
+    pub decoded: Option<DecodedExtension>,
+}
+
+impl From<&X509Extension<'_>> for ExtensionSummary {
+    fn from(extension: &X509Extension) -> Self {
+        let registry = oid::registry();
+        Self {
+            oid: extension.oid.to_id_string(),
+            name: registry.get(&extension.oid).map(|e| e.sn().to_string()),
+            critical: extension.critical,
+            value: format_hex(extension.value),
+            decoded: DecodedExtension::decode(extension),
+        }
+    }
+}
+
+/// A summary of a log entry, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntrySummary {
+    /// Schema version, always [ENTRY_SUMMARY_VERSION] when produced by this crate.
+    pub schema_version: u32,
+    /// Index of the entry in the log, when known.
+    pub index: Option<u64>,
+    /// Absent for unknown versions and leaf types.
+    pub entry_type: Option<LogEntryType>,
+    /// The SCT timestamp, in milliseconds since the epoch. Absent for unknown versions and
+    /// leaf types.
+    pub timestamp: Option<u64>,
+    /// The SCT timestamp, RFC 3339.
+    pub logged_at: Option<String>,
+    /// The logged certificate, absent for unknown entries.
+    #[serde(flatten)]
+    pub certificate: Option<CertificateSummary>,
+    /// The base64 MerkleTreeLeaf of unknown entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_input: Option<String>,
+    /// The base64 extra_data of unknown entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_data: Option<String>,
+}
+
+impl EntrySummary {
+    /// Set the index of the entry in its log.
+    pub fn with_index(mut self, index: u64) -> Self {
+        self.index = Some(index);
+        self
+    }
+}
+
+pub(crate) fn first_common_name(name: &X509Name) -> Option<String> {
+    name.iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok().map(|s| s.to_string()))
+}
+
+pub(crate) fn format_asn1_time(time: &ASN1Time) -> String {
+    Utc.timestamp_opt(time.timestamp(), 0).single().map_or_else(
+        || time.to_string(),
+        |time| time.to_rfc3339_opts(SecondsFormat::Secs, true),
+    )
+}
+
+/// The certificate fields of an [EntrySummary], for certificates outside of a log entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertificateSummary {
+    pub subject: String,
+    /// The first commonName of the subject.
+    pub subject_cn: Option<String>,
+    pub sans: Vec<SubjectAltName>,
+    pub issuer: String,
+    /// The first commonName of the issuer.
+    pub issuer_cn: Option<String>,
+    /// Serial number, colon-separated hex.
+    pub serial: String,
+    /// notBefore, RFC 3339.
+    pub not_before: String,
+    /// notAfter, RFC 3339.
+    pub not_after: String,
+    pub is_ca: bool,
+    /// DV, OV, IV or EV; absent for CA certificates.
+    #[serde(default)]
+    pub validation_level: Option<ValidationLevel>,
+    pub fingerprints: Fingerprints,
+    pub extensions: Vec<ExtensionSummary>,
+}
+
+impl CertificateSummary {
+    fn new(certificate: &(impl CertificateInfo + ?Sized), fingerprints: Fingerprints) -> Self {
+        Self {
+            subject: certificate.subject().to_string(),
+            subject_cn: first_common_name(certificate.subject()),
+            sans: certificate
+                .subject_alternative_names()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            issuer: certificate.issuer().to_string(),
+            issuer_cn: first_common_name(certificate.issuer()),
+            serial: certificate.raw_serial_as_string(),
+            not_before: format_asn1_time(&certificate.validity().not_before),
+            not_after: format_asn1_time(&certificate.validity().not_after),
+            is_ca: certificate.tbs_certificate().is_ca(),
+            validation_level: certificate.validation_level(),
+            fingerprints,
+            extensions: certificate.extensions().iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&WrapX509Certificate> for CertificateSummary {
+    fn from(certificate: &WrapX509Certificate) -> Self {
+        Self::new(certificate, Fingerprints::from_certificate(certificate))
+    }
+}
+
+impl From<&DecodedEntry> for EntrySummary {
+    fn from(entry: &DecodedEntry) -> Self {
+        let certificate =
+            entry
+                .info()
+                .zip(entry.submitted_certificate())
+                .map(|(certificate, submitted)| {
+                    CertificateSummary::new(
+                        certificate,
+                        Fingerprints::from_certificate(&submitted.certificate),
+                    )
+                });
+        let (leaf_input, extra_data) = match &*entry.entry {
+            LogEntry::Unknown {
+                leaf_input,
+                extra_data,
+                ..
+            } => (
+                Some(base64::encode(leaf_input)),
+                Some(base64::encode(extra_data)),
+            ),
+            _ => (None, None),
+        };
+
+        Self {
+            schema_version: ENTRY_SUMMARY_VERSION,
+            index: None,
+            entry_type: entry.entry_type(),
+            timestamp: entry.timestamp,
+            logged_at: entry.timestamp.map(|timestamp| {
+                datetime_from_millis(timestamp).map_or_else(
+                    || timestamp.to_string(),
+                    |time| time.to_rfc3339_opts(SecondsFormat::Millis, true),
+                )
+            }),
+            certificate,
+            leaf_input,
+            extra_data,
+        }
+    }
+}
@@ -0,0 +1,466 @@
+//! Validation of the chains of log entries.
+//!
+//! [RFC 6962 3.1](https://datatracker.ietf.org/doc/html/rfc6962#section-3.1): a log only
+//! accepts chains ending at one of its accepted roots. [ChainValidator] checks that the chain
+//! of a [DecodedEntry] does, with issuer names chaining, signatures verifying (RSA, ECDSA and
+//! Ed25519) and basic constraints respected, to find entries the log should have rejected.
+//!
+//! For precert entries, [check_precert] also checks the logged [PreCert](super::PreCert)
+//! against the precertificate and its chain.
+
+use std::fmt;
+
+use x509_parser::prelude::*;
+
+use super::precert::is_precert_signing_certificate;
+use super::{ASN1Cert, DecodedEntry, LogEntry, PreCert, WrapX509Certificate};
+use crate::ct::oid::OID_CT_PRECERT_POISON;
+
+/// A mismatch between a logged [PreCert] and its [PrecertChainEntry](super::PrecertChainEntry).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrecertProblem {
+    /// The precertificate has no poison extension.
+    MissingPoison,
+    /// The poison extension is not critical.
+    PoisonNotCritical,
+    /// The chain has no issuer (or no final issuer after a Precertificate Signing
+    /// Certificate).
+    MissingIssuer,
+    /// The issuer_key_hash is not the hash of the final issuer's key.
+    IssuerKeyHashMismatch,
+    /// The logged TBSCertificate is not the precertificate's without the poison.
+    TbsMismatch,
+    /// The expected PreCert could not be rebuilt from the precertificate and its issuer.
+    RebuildFailed(String),
+}
+
+impl fmt::Display for PrecertProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrecertProblem::MissingPoison => write!(f, "precertificate has no poison extension"),
+            PrecertProblem::PoisonNotCritical => write!(f, "poison extension is not critical"),
+            PrecertProblem::MissingIssuer => write!(f, "precertificate chain has no issuer"),
+            PrecertProblem::IssuerKeyHashMismatch => {
+                write!(f, "issuer_key_hash does not match the issuer's key")
+            }
+            PrecertProblem::TbsMismatch => {
+                write!(f, "logged TBSCertificate does not match the precertificate")
+            }
+            PrecertProblem::RebuildFailed(error) => {
+                write!(
+                    f,
+                    "cannot rebuild the PreCert from the precertificate: {}",
+                    error
+                )
+            }
+        }
+    }
+}
+
+/// Check a precert entry: the precertificate carries the critical poison extension, and the
+/// logged [PreCert] is the one rebuilt from it and its issuer, as
+/// [PreCert::from_precertificate] does. X.509 and unknown entries have no problems.
+pub fn check_precert(entry: &DecodedEntry) -> Vec<PrecertProblem> {
+    let (pre_cert, chain) = match &*entry.entry {
+        LogEntry::Precert { pre_cert, chain } => (pre_cert, chain),
+        LogEntry::X509 { .. } | LogEntry::Unknown { .. } => return Vec::new(),
+    };
+    let precertificate = chain.pre_certificate.certificate.borrow_certificate();
+    let mut problems = Vec::new();
+
+    match precertificate
+        .extensions()
+        .iter()
+        .find(|extension| extension.oid == OID_CT_PRECERT_POISON)
+    {
+        Some(poison) if !poison.critical => problems.push(PrecertProblem::PoisonNotCritical),
+        Some(_) => {}
+        None => problems.push(PrecertProblem::MissingPoison),
+    }
+
+    let certificates = &chain.precertificate_chain.certificates;
+    let issuer = match certificates.first() {
+        Some(issuer) => issuer.certificate.borrow_certificate(),
+        None => {
+            problems.push(PrecertProblem::MissingIssuer);
+            return problems;
+        }
+    };
+    let final_issuer = certificates
+        .get(1)
+        .map(|certificate| certificate.certificate.borrow_certificate());
+    if is_precert_signing_certificate(issuer) && final_issuer.is_none() {
+        problems.push(PrecertProblem::MissingIssuer);
+        return problems;
+    }
+
+    match PreCert::from_precertificate(precertificate, issuer, final_issuer) {
+        Ok(expected) => {
+            if expected.issuer_key_hash.0 != pre_cert.issuer_key_hash.0 {
+                problems.push(PrecertProblem::IssuerKeyHashMismatch);
+            }
+            if expected.tbs_certificate.as_der() != pre_cert.tbs_certificate.as_der() {
+                problems.push(PrecertProblem::TbsMismatch);
+            }
+        }
+        Err(e) => problems.push(PrecertProblem::RebuildFailed(e.to_string())),
+    }
+
+    problems
+}
+
+/// A reason for a chain to be rejected. Positions are in the path, 0 being the submitted
+/// certificate and the last the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainProblem {
+    /// The issuer of the certificate is not the subject of the next one.
+    IssuerMismatch { position: usize },
+    /// The signature of the certificate does not verify with the next one's key.
+    InvalidSignature { position: usize },
+    /// The signature algorithm of the certificate is not supported.
+    UnsupportedSignature { position: usize, algorithm: String },
+    /// The issuer of the certificate is not a CA.
+    NotCa { position: usize },
+    /// The issuer of the certificate has a path length constraint the path exceeds.
+    PathLengthExceeded { position: usize },
+    /// The chain does not end at an accepted root.
+    UntrustedRoot,
+    /// The logged precertificate does not match its chain, see [check_precert].
+    Precert(PrecertProblem),
+    /// The entry is of an unknown version, leaf type or entry type, and has no chain.
+    UnknownEntry,
+}
+
+impl fmt::Display for ChainProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainProblem::IssuerMismatch { position } => write!(
+                f,
+                "issuer of certificate {} is not the subject of certificate {}",
+                position,
+                position + 1
+            ),
+            ChainProblem::InvalidSignature { position } => {
+                write!(f, "signature of certificate {} does not verify", position)
+            }
+            ChainProblem::UnsupportedSignature {
+                position,
+                algorithm,
+            } => write!(
+                f,
+                "signature algorithm {} of certificate {} is not supported",
+                algorithm, position
+            ),
+            ChainProblem::NotCa { position } => {
+                write!(f, "issuer of certificate {} is not a CA", position)
+            }
+            ChainProblem::PathLengthExceeded { position } => write!(
+                f,
+                "path length constraint of the issuer of certificate {} is exceeded",
+                position
+            ),
+            ChainProblem::UntrustedRoot => write!(f, "chain does not end at an accepted root"),
+            ChainProblem::Precert(problem) => problem.fmt(f),
+            ChainProblem::UnknownEntry => write!(f, "unknown entry has no chain to validate"),
+        }
+    }
+}
+
+/// The result of validating one chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainValidation {
+    pub problems: Vec<ChainProblem>,
+}
+
+impl ChainValidation {
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Validates entry chains against the roots accepted by a log.
+#[derive(Debug)]
+pub struct ChainValidator {
+    roots: Vec<WrapX509Certificate>,
+}
+
+/// Whether `certificate` is `root`: same subject and public key.
+fn is_root(certificate: &X509Certificate, root: &X509Certificate) -> bool {
+    certificate.subject().as_raw() == root.subject().as_raw()
+        && certificate.public_key().raw == root.public_key().raw
+}
+
+impl ChainValidator {
+    /// A validator for a log accepting `roots`, e.g. from
+    /// [get_roots_decoded](crate::CTLogV1::get_roots_decoded).
+    pub fn new(roots: Vec<WrapX509Certificate>) -> Self {
+        Self { roots }
+    }
+
+    /// Validate the chain of `entry`: the submitted certificate (the precertificate of
+    /// precert entries) followed by its chain, and [check_precert].
+    pub fn validate(&self, entry: &DecodedEntry) -> ChainValidation {
+        let (submitted, chain) = match entry.submitted_certificate().zip(entry.chain()) {
+            Some(certificates) => certificates,
+            None => {
+                return ChainValidation {
+                    problems: vec![ChainProblem::UnknownEntry],
+                }
+            }
+        };
+        let mut path: Vec<&X509Certificate> = std::iter::once(submitted)
+            .chain(chain.certificates.iter())
+            .map(|certificate: &ASN1Cert| certificate.certificate.borrow_certificate())
+            .collect();
+
+        let mut problems = Vec::new();
+
+        // The chain should end with the root, which may also be omitted
+        let last = path[path.len() - 1];
+        let anchor = self
+            .roots
+            .iter()
+            .map(|root| root.borrow_certificate())
+            .find(|root| is_root(last, root));
+        if anchor.is_none() {
+            match self
+                .roots
+                .iter()
+                .map(|root| root.borrow_certificate())
+                .find(|root| root.subject().as_raw() == last.issuer().as_raw())
+            {
+                Some(root) => path.push(root),
+                None => problems.push(ChainProblem::UntrustedRoot),
+            }
+        }
+
+        for position in 0..path.len() - 1 {
+            let (certificate, issuer) = (path[position], path[position + 1]);
+
+            if certificate.issuer().as_raw() != issuer.subject().as_raw() {
+                problems.push(ChainProblem::IssuerMismatch { position });
+            }
+
+            match certificate.verify_signature(Some(issuer.public_key())) {
+                Ok(()) => {}
+                Err(X509Error::SignatureUnsupportedAlgorithm) => {
+                    problems.push(ChainProblem::UnsupportedSignature {
+                        position,
+                        algorithm: certificate.signature_algorithm.algorithm.to_id_string(),
+                    })
+                }
+                Err(_) => problems.push(ChainProblem::InvalidSignature { position }),
+            }
+
+            // Version 1 roots have no extensions to check
+            let is_trust_anchor = position + 1 == path.len() - 1;
+            if is_trust_anchor && issuer.version() == X509Version::V1 {
+                continue;
+            }
+            match issuer.basic_constraints() {
+                Ok(Some(constraints)) if constraints.value.ca => {
+                    // Intermediate CAs between the issuer and the submitted certificate
+                    if let Some(path_len) = constraints.value.path_len_constraint {
+                        if position as u32 > path_len {
+                            problems.push(ChainProblem::PathLengthExceeded { position });
+                        }
+                    }
+                }
+                _ => problems.push(ChainProblem::NotCa { position }),
+            }
+        }
+
+        problems.extend(check_precert(entry).into_iter().map(ChainProblem::Precert));
+
+        ChainValidation { problems }
+    }
+
+    /// Validate every entry, returning the position and problems of the invalid ones.
+    pub fn invalid_entries<'a>(
+        &self,
+        entries: impl IntoIterator<Item = &'a DecodedEntry>,
+    ) -> Vec<(usize, ChainValidation)> {
+        entries
+            .into_iter()
+            .map(|entry| self.validate(entry))
+            .enumerate()
+            .filter(|(_, validation)| !validation.is_valid())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ct::v1::tests::{precert_entry, test_certificate, x509_entry};
+
+    fn validator(roots: &[&str]) -> ChainValidator {
+        ChainValidator::new(
+            roots
+                .iter()
+                .map(|root| WrapX509Certificate::from_der(&test_certificate(root)).unwrap())
+                .collect(),
+        )
+    }
+
+    fn problems(validator: &ChainValidator, chain: &[&str]) -> Vec<ChainProblem> {
+        let chain: Vec<_> = chain.iter().map(|name| test_certificate(name)).collect();
+        validator.validate(&x509_entry(&chain)).problems
+    }
+
+    #[test]
+    fn valid_chains() {
+        let validator = validator(&["v1_root.pem", "ca.pem"]);
+        assert_eq!(problems(&validator, &["final.pem", "ca.pem"]), []);
+        // The root may be omitted
+        assert_eq!(problems(&validator, &["final.pem"]), []);
+        // Version 1 roots have no basicConstraints
+        assert_eq!(problems(&validator, &["v1_leaf.pem", "v1_root.pem"]), []);
+        assert_eq!(problems(&validator, &["v1_leaf.pem"]), []);
+        assert_eq!(problems(&validator, &["intermediate.pem", "ca.pem"]), []);
+    }
+
+    #[test]
+    fn invalid_chains() {
+        let validator = validator(&["ca.pem"]);
+
+        assert_eq!(
+            problems(&validator, &["v1_leaf.pem"]),
+            [ChainProblem::UntrustedRoot]
+        );
+        assert_eq!(
+            problems(&validator, &["final.pem", "v1_root.pem"]),
+            [
+                ChainProblem::UntrustedRoot,
+                ChainProblem::IssuerMismatch { position: 0 },
+                ChainProblem::InvalidSignature { position: 0 },
+            ]
+        );
+
+        // Flip a bit of the last byte of the ECDSA signature
+        let mut tampered = test_certificate("final.pem");
+        *tampered.last_mut().unwrap() ^= 1;
+        let entry = x509_entry(&[tampered, test_certificate("ca.pem")]);
+        assert_eq!(
+            validator.validate(&entry).problems,
+            [ChainProblem::InvalidSignature { position: 0 }]
+        );
+
+        assert_eq!(
+            problems(&validator, &["sha512.pem"]),
+            [ChainProblem::UnsupportedSignature {
+                position: 0,
+                algorithm: "1.2.840.10045.4.3.4".into()
+            }]
+        );
+        assert_eq!(
+            problems(&validator, &["leaf_issued.pem", "final.pem"]),
+            [ChainProblem::NotCa { position: 0 }]
+        );
+    }
+
+    #[test]
+    fn path_length() {
+        let validator = validator(&["ca.pem"]);
+
+        // No intermediate CA below the pathLen 0 intermediate
+        assert_eq!(
+            problems(
+                &validator,
+                &["sub_intermediate.pem", "intermediate.pem", "ca.pem"]
+            ),
+            []
+        );
+        assert_eq!(
+            problems(
+                &validator,
+                &["sub_leaf.pem", "sub_intermediate.pem", "intermediate.pem"]
+            ),
+            [ChainProblem::PathLengthExceeded { position: 1 }]
+        );
+    }
+
+    /// The PreCert rebuilt from the first of `certificates`, issued by the next ones.
+    fn pre_cert(certificates: &[Vec<u8>]) -> PreCert {
+        let parsed: Vec<_> = certificates
+            .iter()
+            .map(|der| X509Certificate::from_der(der).unwrap().1)
+            .collect();
+        PreCert::from_precertificate(&parsed[0], &parsed[1], parsed.get(2)).unwrap()
+    }
+
+    fn certificates(names: &[&str]) -> Vec<Vec<u8>> {
+        names.iter().map(|name| test_certificate(name)).collect()
+    }
+
+    #[test]
+    fn matching_precert() {
+        let chain = certificates(&["precert.pem", "ca.pem"]);
+        let entry = precert_entry(pre_cert(&chain), &chain);
+        assert_eq!(check_precert(&entry), []);
+        assert!(validator(&["ca.pem"]).validate(&entry).is_valid());
+
+        // Signed by a Precertificate Signing Certificate: logged as if the CA had signed it
+        let chain = certificates(&["psc_precert.pem", "psc.pem", "ca.pem"]);
+        let entry = precert_entry(pre_cert(&chain), &chain);
+        assert_eq!(check_precert(&entry), []);
+        assert!(validator(&["ca.pem"]).validate(&entry).is_valid());
+        assert_eq!(
+            pre_cert(&chain).issuer_key_hash.0,
+            pre_cert(&certificates(&["precert.pem", "ca.pem"]))
+                .issuer_key_hash
+                .0
+        );
+
+        assert_eq!(check_precert(&x509_entry(&chain)), []);
+    }
+
+    #[test]
+    fn mismatching_precert() {
+        let chain = certificates(&["precert.pem", "ca.pem"]);
+
+        let mut tampered = pre_cert(&chain);
+        tampered.issuer_key_hash.0[0] ^= 1;
+        let entry = precert_entry(tampered, &chain);
+        assert_eq!(
+            check_precert(&entry),
+            [PrecertProblem::IssuerKeyHashMismatch]
+        );
+        assert_eq!(
+            validator(&["ca.pem"]).validate(&entry).problems,
+            [ChainProblem::Precert(PrecertProblem::IssuerKeyHashMismatch)]
+        );
+
+        // The PreCert of another certificate of the same issuer
+        let lifetime = certificates(&["lifetime_180d.pem", "ca.pem"]);
+        let (_, certificate) = X509Certificate::from_der(&lifetime[0]).unwrap();
+        let (_, issuer) = X509Certificate::from_der(&lifetime[1]).unwrap();
+        let entry = precert_entry(
+            PreCert::from_final_certificate(&certificate, &issuer).unwrap(),
+            &chain,
+        );
+        assert_eq!(check_precert(&entry), [PrecertProblem::TbsMismatch]);
+
+        let noncritical = certificates(&["precert_noncritical.pem", "ca.pem"]);
+        let entry = precert_entry(pre_cert(&noncritical), &noncritical);
+        assert_eq!(check_precert(&entry), [PrecertProblem::PoisonNotCritical]);
+
+        let entry = precert_entry(pre_cert(&chain), &certificates(&["final.pem", "ca.pem"]));
+        assert_eq!(
+            check_precert(&entry),
+            [PrecertProblem::MissingPoison, PrecertProblem::TbsMismatch]
+        );
+    }
+
+    #[test]
+    fn precert_without_issuer() {
+        let chain = certificates(&["precert.pem", "ca.pem"]);
+        let entry = precert_entry(pre_cert(&chain), &chain[..1]);
+        assert_eq!(check_precert(&entry), [PrecertProblem::MissingIssuer]);
+
+        // The final issuer of a Precertificate Signing Certificate is missing
+        let chain = certificates(&["psc_precert.pem", "psc.pem", "ca.pem"]);
+        let entry = precert_entry(pre_cert(&chain), &chain[..2]);
+        assert_eq!(check_precert(&entry), [PrecertProblem::MissingIssuer]);
+    }
+}
@@ -12,6 +12,16 @@ pub enum CTLogError {
     DekuError(#[from] deku::DekuError),
     #[error("base64 error: {0}")]
     Base64Error(#[from] base64::DecodeError),
+    #[error("x509 error: {0}")]
+    X509Error(#[from] x509_parser::error::X509Error),
+    #[error("decode error: {0}")]
+    DecodeError(String),
+    #[error("json error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("verification error: {0}")]
+    VerificationError(String),
 }
 
 pub mod client;
@@ -19,4 +29,12 @@ pub use client::CTLogV1;
 
 pub mod ct;
 
+pub mod loglist;
+
+pub mod policy;
+
+pub mod submit;
+
+pub mod truststore;
+
 pub(crate) mod utils;
@@ -0,0 +1,169 @@
+//! Certificate Transparency log lists.
+//!
+//! Models the v3 log list JSON published by Chrome
+//! (<https://www.gstatic.com/ct/log_list/v3/log_list.json>) and Apple, which is what
+//! SCT verification and CT policy evaluation need to know about logs.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::CTLogError;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LogList {
+    pub version: Option<String>,
+    pub log_list_timestamp: Option<DateTime<Utc>>,
+    pub operators: Vec<Operator>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Operator {
+    pub name: String,
+    #[serde(default)]
+    pub email: Vec<String>,
+    #[serde(default)]
+    pub logs: Vec<Log>,
+    /// Static CT API logs, which share the log ID and key format of RFC 6962 logs.
+    #[serde(default)]
+    pub tiled_logs: Vec<Log>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Log {
+    pub description: Option<String>,
+
+    /// The SHA-256 hash of the log's public key, base64 encoded.
+    pub log_id: String,
+
+    /// The log's DER-encoded SubjectPublicKeyInfo, base64 encoded.
+    pub key: String,
+
+    /// The RFC 6962 API endpoint of the log.
+    pub url: Option<String>,
+
+    /// Static CT API endpoints of a tiled log.
+    pub submission_url: Option<String>,
+    pub monitoring_url: Option<String>,
+
+    /// The Maximum Merge Delay, in seconds.
+    #[serde(default = "default_mmd")]
+    pub mmd: u64,
+
+    pub state: Option<LogState>,
+    pub temporal_interval: Option<TemporalInterval>,
+
+    /// "test" for test logs, absent for production logs.
+    pub log_type: Option<String>,
+}
+
+fn default_mmd() -> u64 {
+    86400
+}
+
+/// The state of a log in the log list, see
+/// <https://googlechrome.github.io/CertificateTransparency/log_states.html>.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum LogState {
+    Pending {
+        timestamp: DateTime<Utc>,
+    },
+    Qualified {
+        timestamp: DateTime<Utc>,
+    },
+    Usable {
+        timestamp: DateTime<Utc>,
+    },
+    Readonly {
+        timestamp: DateTime<Utc>,
+        final_tree_head: Option<FinalTreeHead>,
+    },
+    Retired {
+        timestamp: DateTime<Utc>,
+    },
+    Rejected {
+        timestamp: DateTime<Utc>,
+    },
+}
+
+impl LogState {
+    /// When the log entered this state.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            LogState::Pending { timestamp }
+            | LogState::Qualified { timestamp }
+            | LogState::Usable { timestamp }
+            | LogState::Readonly { timestamp, .. }
+            | LogState::Retired { timestamp }
+            | LogState::Rejected { timestamp } => *timestamp,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FinalTreeHead {
+    pub sha256_root_hash: String,
+    pub tree_size: u64,
+}
+
+/// The range of notAfter dates a sharded log accepts.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TemporalInterval {
+    pub start_inclusive: DateTime<Utc>,
+    pub end_exclusive: DateTime<Utc>,
+}
+
+impl Log {
+    /// The decoded log ID.
+    pub fn id(&self) -> Result<Vec<u8>, CTLogError> {
+        Ok(base64::decode(&self.log_id)?)
+    }
+
+    /// The decoded DER SubjectPublicKeyInfo of the log.
+    pub fn key_der(&self) -> Result<Vec<u8>, CTLogError> {
+        Ok(base64::decode(&self.key)?)
+    }
+
+    /// A human readable name: the description, or the URL.
+    pub fn name(&self) -> &str {
+        self.description
+            .as_deref()
+            .or(self.url.as_deref())
+            .or(self.submission_url.as_deref())
+            .unwrap_or(&self.log_id)
+    }
+}
+
+impl LogList {
+    pub fn from_json(json: &str) -> Result<Self, CTLogError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CTLogError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Download a log list, e.g. Chrome's `log_list.json`.
+    pub async fn fetch(url: &str) -> Result<Self, CTLogError> {
+        Ok(reqwest::get(url).await?.json().await?)
+    }
+
+    /// All logs (including tiled logs) with their operator.
+    pub fn logs(&self) -> impl Iterator<Item = (&Operator, &Log)> {
+        self.operators.iter().flat_map(|operator| {
+            operator
+                .logs
+                .iter()
+                .chain(operator.tiled_logs.iter())
+                .map(move |log| (operator, log))
+        })
+    }
+
+    /// Find a log (and its operator) by log ID.
+    pub fn find_by_id(&self, log_id: &[u8]) -> Option<(&Operator, &Log)> {
+        self.logs()
+            .find(|(_, log)| log.id().map(|id| id == log_id).unwrap_or(false))
+    }
+}
@@ -0,0 +1,538 @@
+//! Certificate Transparency compliance policies.
+//!
+//! Evaluates the [Chrome](https://googlechrome.github.io/CertificateTransparency/ct_policy.html)
+//! and [Apple](https://support.apple.com/en-us/103214) CT policies for a certificate and its
+//! verified SCTs, explaining which rule passed or failed.
+//!
+//! Both policies accept a certificate either through its embedded SCTs, or through SCTs
+//! delivered in the TLS extension or a stapled OCSP response:
+//!
+//! - embedded: 2 SCTs for a lifetime up to 180 days, 3 above, from distinct logs that were
+//!   approved when the SCT was issued, at least one of them still approved;
+//! - TLS / OCSP: 2 SCTs from distinct logs approved at the time of check;
+//!
+//! in both cases from at least 2 distinct log operators.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+use x509_parser::prelude::*;
+
+use crate::ct::v1::sct::{LogId, SctStatus, SctVerification};
+use crate::loglist::{Log, LogList, LogState};
+use crate::utils::datetime_from_millis;
+
+/// Longest certificate lifetime, in days, needing only 2 embedded SCTs.
+pub const SHORT_LIFETIME_DAYS: i64 = 180;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CtPolicy {
+    Chrome,
+    Apple,
+}
+
+impl fmt::Display for CtPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CtPolicy::Chrome => write!(f, "Chrome"),
+            CtPolicy::Apple => write!(f, "Apple"),
+        }
+    }
+}
+
+/// How an SCT reached the client.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SctSource {
+    Embedded,
+    TlsExtension,
+    Ocsp,
+}
+
+/// How an SCT was taken into account.
+#[derive(Debug, Clone)]
+pub struct SctAssessment {
+    pub source: SctSource,
+    pub log_id: LogId,
+    /// Name of the log, when found in the log list.
+    pub log: Option<String>,
+    pub operator: Option<String>,
+    pub status: SctStatus,
+    /// Whether the SCT counts towards the policy.
+    pub counted: bool,
+    /// Whether its log is approved at the time of check.
+    pub currently_approved: bool,
+    pub reason: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Rule {
+    /// Enough SCTs from distinct logs.
+    SctCount,
+    /// SCTs from enough distinct log operators.
+    DistinctOperators,
+    /// At least one embedded SCT from a log approved at the time of check.
+    CurrentlyApprovedLog,
+}
+
+#[derive(Debug, Clone)]
+pub struct RuleResult {
+    pub rule: Rule,
+    pub passed: bool,
+    pub required: usize,
+    pub found: usize,
+    pub explanation: String,
+}
+
+/// The result of [evaluate].
+#[derive(Debug, Clone)]
+pub struct PolicyEvaluation {
+    pub policy: CtPolicy,
+    pub compliant: bool,
+    /// Certificate lifetime in days, rounded up.
+    pub lifetime_days: i64,
+    pub scts: Vec<SctAssessment>,
+    /// Rules for embedded SCTs.
+    pub embedded: Vec<RuleResult>,
+    /// Rules for SCTs delivered through TLS or OCSP.
+    pub delivered: Vec<RuleResult>,
+}
+
+impl PolicyEvaluation {
+    pub fn embedded_compliant(&self) -> bool {
+        self.embedded.iter().all(|rule| rule.passed)
+    }
+
+    pub fn delivered_compliant(&self) -> bool {
+        self.delivered.iter().all(|rule| rule.passed)
+    }
+}
+
+/// Whether SCTs of `log` count for `policy`, and whether the log is approved at `now`.
+fn log_acceptance(
+    policy: CtPolicy,
+    log: &Log,
+    sct_timestamp: u64,
+    now: DateTime<Utc>,
+) -> (bool, bool, &'static str) {
+    match &log.state {
+        // A state change recorded in the log list but not yet effective
+        Some(LogState::Retired { timestamp }) if *timestamp > now => {
+            (true, true, "log is approved until its retirement")
+        }
+        Some(state) if state.timestamp() > now => (false, false, "log is not yet approved"),
+        Some(LogState::Usable { .. }) => (true, true, "log is usable"),
+        Some(LogState::Readonly { .. }) => (true, true, "log is read-only"),
+        Some(LogState::Qualified { .. }) => match policy {
+            CtPolicy::Chrome => (true, true, "log is qualified"),
+            CtPolicy::Apple => (false, false, "log is qualified but not yet usable"),
+        },
+        Some(LogState::Retired { timestamp }) => {
+            if datetime_from_millis(sct_timestamp).is_some_and(|issued| issued < *timestamp) {
+                (true, false, "log retired after the SCT was issued")
+            } else {
+                (false, false, "log retired before the SCT was issued")
+            }
+        }
+        Some(LogState::Pending { .. }) => (false, false, "log is pending"),
+        Some(LogState::Rejected { .. }) => (false, false, "log is rejected"),
+        None => (false, false, "log has no state"),
+    }
+}
+
+fn assess(
+    policy: CtPolicy,
+    source: SctSource,
+    verification: &SctVerification,
+    log_list: &LogList,
+    now: DateTime<Utc>,
+) -> SctAssessment {
+    let sct = &verification.sct;
+    let mut assessment = SctAssessment {
+        source,
+        log_id: sct.id,
+        log: None,
+        operator: None,
+        status: verification.status,
+        counted: false,
+        currently_approved: false,
+        reason: String::new(),
+    };
+
+    let (operator, log) = match log_list.find_by_id(&sct.id.0) {
+        Some(found) => found,
+        None => {
+            assessment.reason = "log not in the log list".to_string();
+            return assessment;
+        }
+    };
+    assessment.log = Some(log.name().to_string());
+    assessment.operator = Some(operator.name.clone());
+
+    if verification.status != SctStatus::Valid {
+        assessment.reason = verification.status.to_string();
+        return assessment;
+    }
+
+    let (counted, currently_approved, reason) = log_acceptance(policy, log, sct.timestamp, now);
+    // Only logs approved at the time of check count for TLS and OCSP SCTs
+    assessment.counted = counted && (source == SctSource::Embedded || currently_approved);
+    assessment.currently_approved = currently_approved;
+    assessment.reason = reason.to_string();
+    assessment
+}
+
+fn count_rules(scts: &[&SctAssessment], required_logs: usize) -> Vec<RuleResult> {
+    let logs: HashSet<_> = scts.iter().map(|sct| sct.log_id).collect();
+    let operators: HashSet<_> = scts
+        .iter()
+        .filter_map(|sct| sct.operator.as_ref())
+        .collect();
+
+    vec![
+        RuleResult {
+            rule: Rule::SctCount,
+            passed: logs.len() >= required_logs,
+            required: required_logs,
+            found: logs.len(),
+            explanation: format!(
+                "{} of {} required SCTs from distinct qualifying logs",
+                logs.len(),
+                required_logs
+            ),
+        },
+        RuleResult {
+            rule: Rule::DistinctOperators,
+            passed: operators.len() >= 2,
+            required: 2,
+            found: operators.len(),
+            explanation: format!("{} of 2 required distinct log operators", operators.len()),
+        },
+    ]
+}
+
+/// Evaluate `policy` for `certificate` at time `now`.
+///
+/// `embedded` are the verified SCTs of the SCT list extension, `delivered` those received
+/// through the TLS extension or OCSP (see [verify_scts](crate::ct::v1::sct::verify_scts)).
+pub fn evaluate(
+    policy: CtPolicy,
+    certificate: &TbsCertificate,
+    embedded: &[SctVerification],
+    delivered: &[(SctSource, SctVerification)],
+    log_list: &LogList,
+    now: DateTime<Utc>,
+) -> PolicyEvaluation {
+    let validity = certificate.validity();
+    let lifetime =
+        Duration::seconds(validity.not_after.timestamp() - validity.not_before.timestamp());
+    // Any part of a day counts: 180 days and 1 hour is above 180 days
+    let lifetime_days = (lifetime.num_seconds() + 86399) / 86400;
+    let required_embedded = if lifetime <= Duration::days(SHORT_LIFETIME_DAYS) {
+        2
+    } else {
+        3
+    };
+
+    let scts: Vec<_> = embedded
+        .iter()
+        .map(|sct| assess(policy, SctSource::Embedded, sct, log_list, now))
+        .chain(
+            delivered
+                .iter()
+                .map(|(source, sct)| assess(policy, *source, sct, log_list, now)),
+        )
+        .collect();
+
+    let counted_embedded: Vec<_> = scts
+        .iter()
+        .filter(|sct| sct.source == SctSource::Embedded && sct.counted)
+        .collect();
+    let counted_delivered: Vec<_> = scts
+        .iter()
+        .filter(|sct| sct.source != SctSource::Embedded && sct.counted)
+        .collect();
+
+    let mut embedded_rules = count_rules(&counted_embedded, required_embedded);
+    let current = counted_embedded
+        .iter()
+        .filter(|sct| sct.currently_approved)
+        .count();
+    embedded_rules.push(RuleResult {
+        rule: Rule::CurrentlyApprovedLog,
+        passed: current >= 1,
+        required: 1,
+        found: current,
+        explanation: format!("{} SCTs from logs approved at the time of check", current),
+    });
+    let delivered_rules = count_rules(&counted_delivered, 2);
+
+    let mut evaluation = PolicyEvaluation {
+        policy,
+        compliant: false,
+        lifetime_days,
+        scts,
+        embedded: embedded_rules,
+        delivered: delivered_rules,
+    };
+    evaluation.compliant = evaluation.embedded_compliant() || evaluation.delivered_compliant();
+    evaluation
+}
+
+impl fmt::Display for PolicyEvaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} CT policy: {}",
+            self.policy,
+            if self.compliant {
+                "compliant"
+            } else {
+                "NOT compliant"
+            }
+        )?;
+        writeln!(f, "  Certificate lifetime: {} days", self.lifetime_days)?;
+        writeln!(f, "  SCTs:")?;
+        for sct in &self.scts {
+            writeln!(
+                f,
+                "    [{}] {:?} {} ({}): {}",
+                if sct.counted { "x" } else { " " },
+                sct.source,
+                sct.log.as_deref().unwrap_or("unknown log"),
+                sct.operator.as_deref().unwrap_or("unknown operator"),
+                sct.reason
+            )?;
+        }
+        for (name, rules) in [
+            ("Embedded", &self.embedded),
+            ("TLS / OCSP", &self.delivered),
+        ] {
+            writeln!(f, "  {} SCTs:", name)?;
+            for rule in rules {
+                writeln!(
+                    f,
+                    "    {} {:?}: {}",
+                    if rule.passed { "PASS" } else { "FAIL" },
+                    rule.rule,
+                    rule.explanation
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    use super::*;
+    use crate::ct::v1::sct::SignedCertificateTimestampList;
+    use crate::utils::read_pem_certificates;
+
+    const RETIRED: &str = "2023-06-01T00:00:00Z";
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn log(id: u8, state: &str, timestamp: &str) -> serde_json::Value {
+        json!({
+            "description": format!("Log {}", id),
+            "log_id": base64::encode([id; 32]),
+            "key": "",
+            "state": { state: { "timestamp": timestamp } },
+        })
+    }
+
+    /// Logs 1-2 are usable, 3 retired, 4 qualified and 5 usable in the future, each
+    /// operated by a different operator.
+    fn log_list() -> LogList {
+        let logs = [
+            log(1, "usable", "2022-01-01T00:00:00Z"),
+            log(2, "usable", "2022-01-01T00:00:00Z"),
+            log(3, "retired", RETIRED),
+            log(4, "qualified", "2023-12-01T00:00:00Z"),
+            log(5, "usable", "2024-06-01T00:00:00Z"),
+        ];
+        let operators: Vec<_> = logs
+            .into_iter()
+            .enumerate()
+            .map(|(i, log)| json!({ "name": format!("Operator {}", i + 1), "logs": [log] }))
+            .collect();
+        serde_json::from_value(json!({ "operators": operators })).unwrap()
+    }
+
+    fn sct(log: u8, timestamp: DateTime<Utc>) -> SctVerification {
+        let certificate = read_pem_certificates(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/final.pem"
+        )))
+        .unwrap()
+        .remove(0);
+        let (_, certificate) = X509Certificate::from_der(&certificate).unwrap();
+        let mut sct = SignedCertificateTimestampList::from_certificate(&certificate)
+            .unwrap()
+            .unwrap()
+            .scts()
+            .next()
+            .unwrap()
+            .clone();
+        sct.id = LogId([log; 32]);
+        sct.timestamp = timestamp.timestamp_millis() as u64;
+        SctVerification {
+            sct,
+            status: SctStatus::Valid,
+        }
+    }
+
+    fn certificate(pem: &[u8]) -> Vec<u8> {
+        read_pem_certificates(pem).unwrap().remove(0)
+    }
+
+    fn evaluate_embedded(
+        policy: CtPolicy,
+        certificate: &[u8],
+        embedded: &[SctVerification],
+    ) -> PolicyEvaluation {
+        let (_, certificate) = X509Certificate::from_der(certificate).unwrap();
+        evaluate(policy, &certificate, embedded, &[], &log_list(), now())
+    }
+
+    #[test]
+    fn lifetime_boundary() {
+        let issued = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let scts = [sct(1, issued), sct(2, issued)];
+
+        let short = certificate(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/lifetime_180d.pem"
+        )));
+        let evaluation = evaluate_embedded(CtPolicy::Chrome, &short, &scts);
+        assert_eq!(evaluation.lifetime_days, 180);
+        assert_eq!(evaluation.embedded[0].required, 2);
+        assert!(evaluation.embedded_compliant());
+
+        let long = certificate(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/lifetime_180d_1h.pem"
+        )));
+        let evaluation = evaluate_embedded(CtPolicy::Chrome, &long, &scts);
+        assert_eq!(evaluation.lifetime_days, 181);
+        assert_eq!(evaluation.embedded[0].required, 3);
+        assert!(!evaluation.compliant);
+    }
+
+    #[test]
+    fn retired_log() {
+        let log_list = log_list();
+        let (_, retired) = log_list.find_by_id(&[3; 32]).unwrap();
+        let retirement: DateTime<Utc> = RETIRED.parse().unwrap();
+        let before = (retirement.timestamp_millis() - 1) as u64;
+        let after = retirement.timestamp_millis() as u64;
+
+        assert!(matches!(
+            log_acceptance(CtPolicy::Chrome, retired, before, now()),
+            (true, false, _)
+        ));
+        assert!(matches!(
+            log_acceptance(CtPolicy::Chrome, retired, after, now()),
+            (false, false, _)
+        ));
+        // Retirement recorded in the log list but not yet effective
+        assert!(matches!(
+            log_acceptance(
+                CtPolicy::Apple,
+                retired,
+                before,
+                retirement - Duration::days(1)
+            ),
+            (true, true, _)
+        ));
+        // Timestamps beyond the range of DateTime
+        assert!(matches!(
+            log_acceptance(CtPolicy::Chrome, retired, u64::MAX, now()),
+            (false, false, _)
+        ));
+
+        let short = certificate(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/lifetime_180d.pem"
+        )));
+        let issued = retirement - Duration::days(30);
+        let evaluation =
+            evaluate_embedded(CtPolicy::Chrome, &short, &[sct(1, issued), sct(3, issued)]);
+        assert!(evaluation.embedded_compliant());
+        let evaluation = evaluate_embedded(
+            CtPolicy::Chrome,
+            &short,
+            &[sct(1, issued), sct(3, retirement + Duration::days(1))],
+        );
+        assert!(!evaluation.embedded_compliant());
+    }
+
+    #[test]
+    fn qualified_log() {
+        let log_list = log_list();
+        let (_, qualified) = log_list.find_by_id(&[4; 32]).unwrap();
+        let issued = now().timestamp_millis() as u64;
+        assert!(matches!(
+            log_acceptance(CtPolicy::Chrome, qualified, issued, now()),
+            (true, true, _)
+        ));
+        assert!(matches!(
+            log_acceptance(CtPolicy::Apple, qualified, issued, now()),
+            (false, false, _)
+        ));
+
+        let (_, future) = log_list.find_by_id(&[5; 32]).unwrap();
+        assert!(matches!(
+            log_acceptance(CtPolicy::Chrome, future, issued, now()),
+            (false, false, _)
+        ));
+
+        let short = certificate(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/lifetime_180d.pem"
+        )));
+        let scts = [sct(1, now()), sct(4, now())];
+        assert!(evaluate_embedded(CtPolicy::Chrome, &short, &scts).compliant);
+        assert!(!evaluate_embedded(CtPolicy::Apple, &short, &scts).compliant);
+    }
+
+    #[test]
+    fn delivered_scts_need_current_logs() {
+        let short = certificate(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/lifetime_180d.pem"
+        )));
+        let (_, short) = X509Certificate::from_der(&short).unwrap();
+        let issued: DateTime<Utc> = "2023-01-01T00:00:00Z".parse().unwrap();
+
+        let delivered = [
+            (SctSource::TlsExtension, sct(1, issued)),
+            (SctSource::Ocsp, sct(3, issued)),
+        ];
+        let evaluation = evaluate(
+            CtPolicy::Chrome,
+            &short,
+            &[],
+            &delivered,
+            &log_list(),
+            now(),
+        );
+        assert!(!evaluation.scts[1].counted);
+        assert!(!evaluation.delivered_compliant());
+        assert!(!evaluation.compliant);
+
+        let delivered = [
+            (SctSource::TlsExtension, sct(1, issued)),
+            (SctSource::Ocsp, sct(2, issued)),
+        ];
+        let evaluation = evaluate(CtPolicy::Apple, &short, &[], &delivered, &log_list(), now());
+        assert!(evaluation.delivered_compliant());
+        assert!(evaluation.compliant);
+    }
+}
@@ -0,0 +1,243 @@
+//! Submission of a certificate chain to several logs.
+//!
+//! [submit_chain] picks the logs whose accepted roots cover the chain, submits it to all of
+//! them concurrently (with `add-pre-chain` for precertificates), verifies every returned
+//! SCT and collects them into a [SignedCertificateTimestampList].
+
+use futures_util::future::join_all;
+use x509_parser::prelude::*;
+
+use crate::ct::v1::precert::is_precertificate;
+use crate::ct::v1::sct::{SignedCertificateTimestamp, SignedCertificateTimestampList};
+use crate::ct::v1::{ASN1Cert, PreCert, TimestampedEntrySignedInner};
+use crate::loglist::Log;
+use crate::utils::{read_der_tlv, read_pem_certificates};
+use crate::{CTLogError, CTLogV1};
+
+/// Read a chain, leaf first, from PEM (any number of `CERTIFICATE` blocks) or from
+/// concatenated DER certificates.
+pub fn read_chain(bytes: &[u8]) -> Result<Vec<Vec<u8>>, CTLogError> {
+    let start = bytes
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    // The PEM reader only recognizes headers at the start of a line
+    if bytes[start..].starts_with(b"-----BEGIN") {
+        return read_pem_certificates(&bytes[start..]);
+    }
+
+    let mut chain = Vec::new();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (_, _, next) = read_der_tlv(rest)
+            .ok_or_else(|| CTLogError::DecodeError("invalid DER certificate".into()))?;
+        chain.push(rest[..rest.len() - next.len()].to_vec());
+        rest = next;
+    }
+    Ok(chain)
+}
+
+/// The outcome of submitting to one log.
+#[derive(Debug)]
+pub enum SubmissionOutcome {
+    /// The log returned an SCT whose signature verifies.
+    Submitted(SignedCertificateTimestamp),
+    /// The log does not accept the root of the chain.
+    RootNotAccepted,
+    /// The log returned an SCT which does not verify with its key.
+    InvalidSct(SignedCertificateTimestamp),
+    Failed(CTLogError),
+}
+
+#[derive(Debug)]
+pub struct LogSubmission {
+    /// Name of the log, see [Log::name].
+    pub log: String,
+    pub outcome: SubmissionOutcome,
+}
+
+#[derive(Debug)]
+pub struct ChainSubmission {
+    pub submissions: Vec<LogSubmission>,
+    /// The verified SCTs, ready for embedding or TLS delivery.
+    pub sct_list: SignedCertificateTimestampList,
+}
+
+/// Whether `roots` (DER) include the root of `chain` (DER, leaf first).
+///
+/// The chain may end with the root itself, or with a certificate issued by the root.
+fn chain_accepted(chain: &[Vec<u8>], roots: &[Vec<u8>]) -> Result<bool, CTLogError> {
+    if chain.iter().any(|certificate| roots.contains(certificate)) {
+        return Ok(true);
+    }
+    let last = chain
+        .last()
+        .ok_or_else(|| CTLogError::DecodeError("empty chain".into()))?;
+    let last = parse(last)?;
+    Ok(roots.iter().any(|root| {
+        parse(root)
+            .map(|root| root.subject().as_raw() == last.issuer().as_raw())
+            .unwrap_or(false)
+    }))
+}
+
+fn parse(der: &[u8]) -> Result<X509Certificate<'_>, CTLogError> {
+    Ok(X509Certificate::from_der(der).map_err(X509Error::from)?.1)
+}
+
+/// The entry the logs sign for `chain`.
+fn signed_entry(chain: &[Vec<u8>]) -> Result<TimestampedEntrySignedInner, CTLogError> {
+    let leaf = parse(&chain[0])?;
+    if !is_precertificate(&leaf) {
+        return Ok(TimestampedEntrySignedInner::X509(ASN1Cert::from_der(
+            &chain[0],
+        )?));
+    }
+
+    let issuer = chain
+        .get(1)
+        .ok_or_else(|| CTLogError::DecodeError("precertificate without issuer".into()))?;
+    let issuer = parse(issuer)?;
+    let final_issuer = chain.get(2).map(|der| parse(der)).transpose()?;
+    Ok(TimestampedEntrySignedInner::Precert(
+        PreCert::from_precertificate(&leaf, &issuer, final_issuer.as_ref())?,
+    ))
+}
+
+async fn submit_to_log(
+    chain: &[Vec<u8>],
+    entry: &TimestampedEntrySignedInner,
+    log: &Log,
+) -> SubmissionOutcome {
+    let submit = async {
+        let url = log
+            .url
+            .as_deref()
+            .or(log.submission_url.as_deref())
+            .ok_or_else(|| CTLogError::DecodeError("log has no submission URL".into()))?;
+        let client = CTLogV1::new(url)?;
+
+        let roots = client
+            .get_roots()
+            .await?
+            .certificates
+            .iter()
+            .map(base64::decode)
+            .collect::<Result<Vec<_>, _>>()?;
+        if !chain_accepted(chain, &roots)? {
+            return Ok(SubmissionOutcome::RootNotAccepted);
+        }
+
+        let encoded = chain.iter().map(base64::encode).collect();
+        let response = match entry {
+            TimestampedEntrySignedInner::X509(_) => client.add_chain(encoded).await?,
+            TimestampedEntrySignedInner::Precert(_) => client.add_pre_chain(encoded).await?,
+            TimestampedEntrySignedInner::Unknown(_) => {
+                return Err(CTLogError::DecodeError("unknown LogEntryType".into()))
+            }
+        };
+        let sct = SignedCertificateTimestamp::try_from(&response)?;
+
+        if sct.verify_signature(entry, &log.key_der()?)? {
+            Ok(SubmissionOutcome::Submitted(sct))
+        } else {
+            Ok(SubmissionOutcome::InvalidSct(sct))
+        }
+    };
+
+    submit.await.unwrap_or_else(SubmissionOutcome::Failed)
+}
+
+/// Submit `chain` (DER, leaf first) to every log of `logs` accepting its root.
+pub async fn submit_chain(chain: &[Vec<u8>], logs: &[&Log]) -> Result<ChainSubmission, CTLogError> {
+    if chain.is_empty() {
+        return Err(CTLogError::DecodeError("empty chain".into()));
+    }
+    let entry = signed_entry(chain)?;
+
+    let outcomes = join_all(logs.iter().map(|log| submit_to_log(chain, &entry, log))).await;
+
+    let submissions: Vec<_> = logs
+        .iter()
+        .zip(outcomes)
+        .map(|(log, outcome)| LogSubmission {
+            log: log.name().to_string(),
+            outcome,
+        })
+        .collect();
+    let scts = submissions
+        .iter()
+        .filter_map(|submission| match &submission.outcome {
+            SubmissionOutcome::Submitted(sct) => Some(sct.clone()),
+            _ => None,
+        })
+        .collect();
+
+    Ok(ChainSubmission {
+        submissions,
+        sct_list: SignedCertificateTimestampList::from_scts(scts),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pem(name: &str) -> Vec<u8> {
+        std::fs::read(format!(
+            "{}/tests/data/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        ))
+        .unwrap()
+    }
+
+    fn der(name: &str) -> Vec<u8> {
+        read_pem_certificates(&pem(name)).unwrap().remove(0)
+    }
+
+    #[test]
+    fn chain_formats() {
+        let expected = vec![der("final.pem"), der("ca.pem")];
+
+        let mut bundle = b"\n  ".to_vec();
+        bundle.extend(pem("final.pem"));
+        bundle.extend(pem("ca.pem"));
+        assert_eq!(read_chain(&bundle).unwrap(), expected);
+
+        assert_eq!(read_chain(&expected.concat()).unwrap(), expected);
+        assert!(read_chain(&[]).unwrap().is_empty());
+
+        let concatenated = expected.concat();
+        assert!(read_chain(&concatenated[..concatenated.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn accepted_roots() {
+        let roots = vec![der("v1_root.pem"), der("ca.pem")];
+
+        assert!(chain_accepted(&[der("final.pem"), der("ca.pem")], &roots).unwrap());
+        // The root is omitted, the issuer of the last certificate is a root
+        assert!(chain_accepted(&[der("final.pem")], &roots).unwrap());
+        assert!(chain_accepted(
+            &[der("sub_intermediate.pem"), der("intermediate.pem")],
+            &roots
+        )
+        .unwrap());
+
+        assert!(!chain_accepted(&[der("sub_intermediate.pem")], &roots).unwrap());
+        assert!(!chain_accepted(&[der("final.pem")], &[der("v1_root.pem")]).unwrap());
+        assert!(chain_accepted(&[], &roots).is_err());
+    }
+
+    #[test]
+    fn signed_entries() {
+        let entry = signed_entry(&[der("final.pem"), der("ca.pem")]).unwrap();
+        assert!(matches!(entry, TimestampedEntrySignedInner::X509(_)));
+
+        let entry = signed_entry(&[der("precert.pem"), der("ca.pem")]).unwrap();
+        assert!(matches!(entry, TimestampedEntrySignedInner::Precert(_)));
+
+        assert!(signed_entry(&[der("precert.pem")]).is_err());
+    }
+}
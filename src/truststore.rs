@@ -0,0 +1,340 @@
+//! Local trust stores, to review the roots accepted by a log.
+//!
+//! A [TrustStore] is loaded from a PEM bundle or from Mozilla's `certdata.txt`, and
+//! [compared](TrustStore::compare) with the decoded `get-roots` of a log.
+
+use std::fmt;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+
+use crate::ct::v1::roots::RootSummary;
+use crate::ct::v1::{CertificateInfo, WrapX509Certificate};
+use crate::utils::{read_pem_certificates, sha1};
+use crate::CTLogError;
+
+#[derive(Debug)]
+pub struct TrustedRoot {
+    /// The `CKA_LABEL` of the certificate in `certdata.txt`, if any.
+    pub label: Option<String>,
+    pub certificate: WrapX509Certificate,
+}
+
+#[derive(Debug, Default)]
+pub struct TrustStore {
+    pub roots: Vec<TrustedRoot>,
+}
+
+/// Roots are the same when they have the same subject and public key, so that a root
+/// re-issued with a new validity period is still recognised.
+fn same_root(a: &WrapX509Certificate, b: &WrapX509Certificate) -> bool {
+    a.subject().as_raw() == b.subject().as_raw() && a.public_key().raw == b.public_key().raw
+}
+
+/// Decode a `MULTILINE_OCTAL` value of `certdata.txt`.
+fn decode_octal(lines: &[&str]) -> Result<Vec<u8>, CTLogError> {
+    let mut bytes = Vec::new();
+    for line in lines {
+        // Also drops the `\r` of CRLF line endings
+        for octal in line.trim().split('\\').filter(|octal| !octal.is_empty()) {
+            bytes.push(u8::from_str_radix(octal, 8).map_err(|_| {
+                CTLogError::DecodeError(format!("invalid octal value in certdata.txt: {octal}"))
+            })?);
+        }
+    }
+    Ok(bytes)
+}
+
+/// One object of `certdata.txt`: its attributes and the decoded octal values.
+#[derive(Default)]
+struct CertdataObject<'a> {
+    attributes: Vec<(&'a str, &'a str)>,
+    values: Vec<(&'a str, Vec<u8>)>,
+}
+
+impl<'a> CertdataObject<'a> {
+    fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| *value)
+    }
+
+    fn value(&self, name: &str) -> Option<&[u8]> {
+        self.values
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| value.as_slice())
+    }
+}
+
+fn parse_certdata(certdata: &str) -> Result<Vec<CertdataObject<'_>>, CTLogError> {
+    let mut objects = Vec::new();
+    let mut object = CertdataObject::default();
+    let mut lines = certdata.lines();
+
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() {
+            if !object.attributes.is_empty() || !object.values.is_empty() {
+                objects.push(std::mem::take(&mut object));
+            }
+            continue;
+        }
+        if line.starts_with('#') || !line.starts_with("CKA_") {
+            continue;
+        }
+
+        let mut parts = line.splitn(3, ' ');
+        let name = parts.next().unwrap_or_default();
+        let kind = parts.next().unwrap_or_default();
+        if kind == "MULTILINE_OCTAL" {
+            let octal: Vec<_> = lines
+                .by_ref()
+                .take_while(|line| line.trim() != "END")
+                .collect();
+            object.values.push((name, decode_octal(&octal)?));
+        } else {
+            object
+                .attributes
+                .push((name, parts.next().unwrap_or_default().trim_matches('"')));
+        }
+    }
+    if !object.attributes.is_empty() || !object.values.is_empty() {
+        objects.push(object);
+    }
+
+    Ok(objects)
+}
+
+impl TrustStore {
+    /// Load every `CERTIFICATE` of a PEM bundle.
+    pub fn from_pem(bundle: &[u8]) -> Result<Self, CTLogError> {
+        let roots = read_pem_certificates(bundle)?
+            .iter()
+            .map(|der| {
+                Ok(TrustedRoot {
+                    label: None,
+                    certificate: WrapX509Certificate::from_der(der)?,
+                })
+            })
+            .collect::<Result<_, CTLogError>>()?;
+        Ok(Self { roots })
+    }
+
+    /// Load the roots trusted to issue server certificates from Mozilla's `certdata.txt`.
+    ///
+    /// Certificates are only included when their trust object has `CKA_TRUST_SERVER_AUTH`
+    /// set to `CKT_NSS_TRUSTED_DELEGATOR`.
+    pub fn from_certdata(certdata: &str) -> Result<Self, CTLogError> {
+        let objects = parse_certdata(certdata)?;
+
+        let trusted: Vec<_> = objects
+            .iter()
+            .filter(|object| {
+                object.attribute("CKA_CLASS") == Some("CKO_NSS_TRUST")
+                    && object.attribute("CKA_TRUST_SERVER_AUTH")
+                        == Some("CKT_NSS_TRUSTED_DELEGATOR")
+            })
+            .filter_map(|object| object.value("CKA_CERT_SHA1_HASH"))
+            .collect();
+
+        let mut roots = Vec::new();
+        for object in &objects {
+            if object.attribute("CKA_CLASS") != Some("CKO_CERTIFICATE") {
+                continue;
+            }
+            let der = match object.value("CKA_VALUE") {
+                Some(der) => der,
+                None => continue,
+            };
+            if trusted.contains(&&sha1(der)[..]) {
+                roots.push(TrustedRoot {
+                    label: object.attribute("CKA_LABEL").map(|label| label.to_string()),
+                    certificate: WrapX509Certificate::from_der(der)?,
+                });
+            }
+        }
+
+        Ok(Self { roots })
+    }
+
+    /// Load a PEM bundle, or `certdata.txt` if the file contains `CKA_CLASS`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CTLogError> {
+        let data = std::fs::read(path)?;
+        match std::str::from_utf8(&data) {
+            Ok(text) if text.contains("CKA_CLASS") => Self::from_certdata(text),
+            _ => Self::from_pem(&data),
+        }
+    }
+
+    pub fn contains(&self, certificate: &WrapX509Certificate) -> bool {
+        self.roots
+            .iter()
+            .any(|root| same_root(&root.certificate, certificate))
+    }
+
+    /// Compare the trust store with the roots accepted by a log, at time `now`.
+    pub fn compare(
+        &self,
+        log_roots: &[WrapX509Certificate],
+        now: DateTime<Utc>,
+    ) -> TrustStoreComparison {
+        TrustStoreComparison {
+            accepted_untrusted: log_roots
+                .iter()
+                .filter(|root| !self.contains(root))
+                .map(Into::into)
+                .collect(),
+            trusted_rejected: self
+                .roots
+                .iter()
+                .filter(|root| {
+                    !log_roots
+                        .iter()
+                        .any(|log_root| same_root(&root.certificate, log_root))
+                })
+                .map(|root| (&root.certificate).into())
+                .collect(),
+            expired_accepted: log_roots
+                .iter()
+                .filter(|root| root.validity().not_after.timestamp() < now.timestamp())
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+/// The result of [TrustStore::compare].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustStoreComparison {
+    /// Roots accepted by the log but not in the trust store.
+    pub accepted_untrusted: Vec<RootSummary>,
+    /// Roots of the trust store the log does not accept.
+    pub trusted_rejected: Vec<RootSummary>,
+    /// Roots accepted by the log which have expired.
+    pub expired_accepted: Vec<RootSummary>,
+}
+
+impl fmt::Display for TrustStoreComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (title, roots) in [
+            (
+                "Accepted by the log but not trusted",
+                &self.accepted_untrusted,
+            ),
+            ("Trusted but rejected by the log", &self.trusted_rejected),
+            ("Expired but accepted by the log", &self.expired_accepted),
+        ] {
+            writeln!(f, "{} ({}):", title, roots.len())?;
+            for root in roots {
+                writeln!(
+                    f,
+                    "  {} {} (not after {})",
+                    root.sha256, root.subject, root.not_after
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/ca.pem"));
+
+    fn octal(der: &[u8]) -> String {
+        der.chunks(16)
+            .map(|line| {
+                line.iter()
+                    .map(|byte| format!("\\{byte:03o}"))
+                    .collect::<String>()
+                    + "\r\n"
+            })
+            .collect()
+    }
+
+    #[test]
+    fn octal_line_endings() {
+        assert_eq!(decode_octal(&["\\060\\061\r", " \\062 "]).unwrap(), b"012");
+        assert!(decode_octal(&["\\060\\8"]).is_err());
+    }
+
+    #[test]
+    fn certdata_with_crlf() {
+        let der = read_pem_certificates(CA).unwrap().remove(0);
+        let certdata = format!(
+            "# Test certdata.txt\r\n\r\n\
+             CKA_CLASS CK_OBJECT_CLASS CKO_CERTIFICATE\r\n\
+             CKA_LABEL UTF8 \"Test CA\"\r\n\
+             CKA_VALUE MULTILINE_OCTAL\r\n{}END\r\n\r\n\
+             CKA_CLASS CK_OBJECT_CLASS CKO_NSS_TRUST\r\n\
+             CKA_CERT_SHA1_HASH MULTILINE_OCTAL\r\n{}END\r\n\
+             CKA_TRUST_SERVER_AUTH CK_TRUST CKT_NSS_TRUSTED_DELEGATOR\r\n",
+            octal(&der),
+            octal(&sha1(&der)),
+        );
+
+        let store = TrustStore::from_certdata(&certdata).unwrap();
+        assert_eq!(store.roots.len(), 1);
+        assert_eq!(store.roots[0].label.as_deref(), Some("Test CA"));
+        assert!(store.contains(&TrustStore::from_pem(CA).unwrap().roots[0].certificate));
+
+        let untrusted = certdata.replace("CKT_NSS_TRUSTED_DELEGATOR", "CKT_NSS_MUST_VERIFY_TRUST");
+        assert!(TrustStore::from_certdata(&untrusted)
+            .unwrap()
+            .roots
+            .is_empty());
+    }
+
+    #[test]
+    fn compare_with_log_roots() {
+        let root = |name: &str| {
+            let pem = std::fs::read(format!(
+                "{}/tests/data/{}",
+                env!("CARGO_MANIFEST_DIR"),
+                name
+            ))
+            .unwrap();
+            TrustStore::from_pem(&pem)
+                .unwrap()
+                .roots
+                .remove(0)
+                .certificate
+        };
+        let store = TrustStore {
+            roots: ["ca.pem", "intermediate.pem"]
+                .into_iter()
+                .map(|name| TrustedRoot {
+                    label: None,
+                    certificate: root(name),
+                })
+                .collect(),
+        };
+        let log_roots = [root("ca.pem"), root("v1_root.pem")];
+
+        // Both log roots expire at 2032-10-29T00:00:00Z
+        let not_after = "2032-10-29T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let comparison = store.compare(&log_roots, not_after);
+        assert_eq!(
+            comparison.accepted_untrusted,
+            [RootSummary::from(&log_roots[1])]
+        );
+        assert_eq!(
+            comparison.trusted_rejected,
+            [RootSummary::from(&store.roots[1].certificate)]
+        );
+        assert!(comparison.expired_accepted.is_empty());
+
+        let comparison = store.compare(&log_roots, not_after + chrono::Duration::seconds(1));
+        assert_eq!(
+            comparison.expired_accepted,
+            [
+                RootSummary::from(&log_roots[0]),
+                RootSummary::from(&log_roots[1])
+            ]
+        );
+    }
+}
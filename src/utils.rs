@@ -3,14 +3,20 @@
 use std::net::{Ipv4Addr, Ipv6Addr};
 use std::{cmp::min, fmt};
 
-use oid_registry::{format_oid, Oid, OidRegistry};
+use chrono::{DateTime, TimeZone, Utc};
+use oid_registry::{format_oid, Oid, OID_SIG_ED25519, OID_SIG_ED448};
+use x509_parser::der_parser::der::parse_der_integer;
 use x509_parser::nom::HexDisplay;
+use x509_parser::pem::Pem;
 use x509_parser::prelude::{GeneralName, ParsedExtension, X509Extension};
 use x509_parser::utils::format_serial;
-#[allow(unused_imports)]
 use x509_parser::{public_key::PublicKey, x509::SubjectPublicKeyInfo};
 
-#[allow(dead_code)]
+use crate::ct::oid::{self, OID_CT_LIST_SCT};
+use crate::ct::v1::extensions::DecodedExtension;
+use crate::ct::v1::sct::SignedCertificateTimestampList;
+use crate::CTLogError;
+
 fn format_hex_dump(bytes: &[u8], max_len: usize) -> String {
     let m = min(bytes.len(), max_len);
     if bytes.len() > max_len {
@@ -20,7 +26,6 @@ fn format_hex_dump(bytes: &[u8], max_len: usize) -> String {
     }
 }
 
-#[allow(dead_code)]
 fn format_number_to_hex_with_colon(b: &[u8], row_size: usize) -> Vec<String> {
     let mut v = Vec::with_capacity(1 + b.len() / row_size);
     for r in b.chunks(row_size) {
@@ -32,6 +37,154 @@ fn format_number_to_hex_with_colon(b: &[u8], row_size: usize) -> Vec<String> {
     v
 }
 
+/// Format a digest as colon-separated uppercase hex, like `openssl x509 -fingerprint`.
+pub fn format_fingerprint(digest: &[u8]) -> String {
+    digest
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Format bytes as lowercase hex without separators.
+pub fn format_hex(b: &[u8]) -> String {
+    b.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Format an iPAddress GeneralName (4 or 16 bytes).
+pub fn format_ip_address(b: &[u8]) -> String {
+    match b.len() {
+        4 => {
+            let b = <[u8; 4]>::try_from(b).unwrap();
+            format!("{}", Ipv4Addr::from(b))
+        }
+        16 => {
+            let b = <[u8; 16]>::try_from(b).unwrap();
+            format!("{}", Ipv6Addr::from(b))
+        }
+        l => format!("invalid (len={})", l),
+    }
+}
+
+/// A timestamp in milliseconds since the epoch, `None` when it is out of range.
+pub fn datetime_from_millis(millis: u64) -> Option<DateTime<Utc>> {
+    i64::try_from(millis)
+        .ok()
+        .and_then(|millis| Utc.timestamp_millis_opt(millis).single())
+}
+
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, data)
+        .as_ref()
+        .try_into()
+        .unwrap()
+}
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    ring::digest::digest(&ring::digest::SHA256, data)
+        .as_ref()
+        .try_into()
+        .unwrap()
+}
+
+/// Print a big-endian number as openssl does: 15 colon-separated bytes per row.
+pub fn print_hex_rows(f: &mut fmt::Formatter<'_>, b: &[u8], indent: usize) -> fmt::Result {
+    let rows = format_number_to_hex_with_colon(b, 15);
+    let last = rows.len().saturating_sub(1);
+    for (i, row) in rows.iter().enumerate() {
+        let row = if i == last {
+            row.trim_end_matches(':')
+        } else {
+            row
+        };
+        writeln!(f, "{:indent$}{}", "", row, indent = indent)?;
+    }
+    Ok(())
+}
+
+/// Read one DER TLV, returning (tag, content, rest). Only single-byte tags are supported.
+pub fn read_der_tlv(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, input) = input.split_first()?;
+    let (&first, mut input) = input.split_first()?;
+    let length = if first < 0x80 {
+        first as usize
+    } else {
+        let n = (first & 0x7f) as usize;
+        if n == 0 || n > 4 || input.len() < n {
+            return None;
+        }
+        let (bytes, rest) = input.split_at(n);
+        input = rest;
+        bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize)
+    };
+    if input.len() < length {
+        return None;
+    }
+    let (content, rest) = input.split_at(length);
+    Some((tag, content, rest))
+}
+
+/// Encode one DER TLV with a single-byte tag.
+pub fn encode_der_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut output = vec![tag];
+    let length = content.len();
+    if length < 0x80 {
+        output.push(length as u8);
+    } else {
+        let bytes = length.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        output.push(0x80 | (bytes.len() - skip) as u8);
+        output.extend_from_slice(&bytes[skip..]);
+    }
+    output.extend_from_slice(content);
+    output
+}
+
+/// Encode DER as PEM with the given label, in lines of 64 characters.
+pub fn encode_pem(label: &str, der: &[u8]) -> String {
+    let encoded = base64::encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+    pem
+}
+
+/// Read the DER of every `CERTIFICATE` of a PEM bundle, in order.
+pub fn read_pem_certificates(bundle: &[u8]) -> Result<Vec<Vec<u8>>, CTLogError> {
+    let mut certificates = Vec::new();
+    for pem in Pem::iter_from_buffer(bundle) {
+        let pem = pem.map_err(|e| CTLogError::DecodeError(format!("invalid PEM: {e}")))?;
+        if pem.label == "CERTIFICATE" {
+            certificates.push(pem.contents);
+        }
+    }
+    Ok(certificates)
+}
+
+/// Size in bits of a big-endian unsigned number, ignoring leading zeros.
+fn bit_length(b: &[u8]) -> usize {
+    match b.iter().position(|&byte| byte != 0) {
+        Some(i) => (b.len() - i) * 8 - b[i].leading_zeros() as usize,
+        None => 0,
+    }
+}
+
+/// OpenSSL names (ASN1 OID, NIST name) and size in bits of the common named curves.
+fn ec_curve_name(oid: &Oid) -> Option<(&'static str, Option<&'static str>, usize)> {
+    match oid.to_id_string().as_str() {
+        "1.2.840.10045.3.1.7" => Some(("prime256v1", Some("P-256"), 256)),
+        "1.3.132.0.34" => Some(("secp384r1", Some("P-384"), 384)),
+        "1.3.132.0.35" => Some(("secp521r1", Some("P-521"), 521)),
+        "1.3.132.0.10" => Some(("secp256k1", None, 256)),
+        "1.2.840.10045.3.1.1" => Some(("prime192v1", Some("P-192"), 192)),
+        "1.3.132.0.33" => Some(("secp224r1", Some("P-224"), 224)),
+        _ => None,
+    }
+}
+
 pub fn print_x509_ski(
     f: &mut fmt::Formatter<'_>,
     public_key: &SubjectPublicKeyInfo,
@@ -41,56 +194,143 @@ pub fn print_x509_ski(
         f,
         "{:indent$}Public Key Algorithm: {}",
         "",
-        format_oid(
-            public_key.algorithm.oid(),
-            &OidRegistry::default().with_all_crypto()
-        ),
+        format_oid(public_key.algorithm.oid(), oid::registry()),
         indent = indent,
     )?;
-    // match public_key.parsed() {
-    //     Ok(PublicKey::RSA(rsa)) => {
-    //         writeln!(
-    //             f,
-    //             "{:indent$}RSA Public Key: ({} bit)",
-    //             "",
-    //             rsa.key_size(),
-    //             indent = indent + 2,
-    //         )?;
-    //         for l in format_number_to_hex_with_colon(rsa.modulus, 16) {
-    //             writeln!(f, "{:indent$}{}", "", l, indent = indent + 4)?;
-    //         }
-    //         if let Ok(e) = rsa.try_exponent() {
-    //             writeln!(
-    //                 f,
-    //                 "{:indent$}Exponent: {} (0x{:x})",
-    //                 "",
-    //                 e,
-    //                 e,
-    //                 indent = indent + 2,
-    //             )?;
-    //         } else {
-    //             writeln!(f, "{:indent$}Exponent: <Invalid>", "", indent = indent + 2)?;
-    //             writeln!(
-    //                 f,
-    //                 "{:indent$}{}",
-    //                 "",
-    //                 format_hex_dump(rsa.exponent, 32),
-    //                 indent = indent + 4
-    //             )?;
-    //         }
-    //     }
-    //     Ok(_) => {
-    //         writeln!(
-    //             f,
-    //             "{:indent$}Unsupported public key algorithm",
-    //             "",
-    //             indent = indent + 2
-    //         )?;
-    //     }
-    //     Err(_) => {
-    //         writeln!(f, "{:indent$}Invalid Public Key", "", indent = indent + 2)?;
-    //     }
-    // }
+
+    if public_key.algorithm.oid() == &OID_SIG_ED25519
+        || public_key.algorithm.oid() == &OID_SIG_ED448
+    {
+        let name = if public_key.algorithm.oid() == &OID_SIG_ED25519 {
+            "ED25519"
+        } else {
+            "ED448"
+        };
+        writeln!(f, "{:indent$}{} Public-Key:", "", name, indent = indent + 2)?;
+        writeln!(f, "{:indent$}pub:", "", indent = indent + 2)?;
+        return print_hex_rows(f, &public_key.subject_public_key.data, indent + 4);
+    }
+
+    match public_key.parsed() {
+        Ok(PublicKey::RSA(rsa)) => {
+            writeln!(
+                f,
+                "{:indent$}Public-Key: ({} bit)",
+                "",
+                bit_length(rsa.modulus),
+                indent = indent + 2,
+            )?;
+            writeln!(f, "{:indent$}Modulus:", "", indent = indent + 2)?;
+            print_hex_rows(f, rsa.modulus, indent + 4)?;
+            if let Ok(e) = rsa.try_exponent() {
+                writeln!(
+                    f,
+                    "{:indent$}Exponent: {} (0x{:x})",
+                    "",
+                    e,
+                    e,
+                    indent = indent + 2,
+                )?;
+            } else {
+                writeln!(f, "{:indent$}Exponent: <Invalid>", "", indent = indent + 2)?;
+                writeln!(
+                    f,
+                    "{:indent$}{}",
+                    "",
+                    format_hex_dump(rsa.exponent, 32),
+                    indent = indent + 4
+                )?;
+            }
+        }
+        Ok(PublicKey::EC(ec)) => {
+            let curve = public_key
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|p| p.as_oid().ok());
+            let named = curve.as_ref().and_then(ec_curve_name);
+            // key_size() is derived from the point length, which rounds P-521 up to 528 bits
+            let bits = named.map_or_else(|| ec.key_size(), |(_, _, bits)| bits);
+            writeln!(
+                f,
+                "{:indent$}Public-Key: ({} bit)",
+                "",
+                bits,
+                indent = indent + 2,
+            )?;
+            writeln!(f, "{:indent$}pub:", "", indent = indent + 2)?;
+            print_hex_rows(f, ec.data(), indent + 4)?;
+            match (curve, named) {
+                (Some(_), Some((name, nist, _))) => {
+                    writeln!(f, "{:indent$}ASN1 OID: {}", "", name, indent = indent + 2)?;
+                    if let Some(nist) = nist {
+                        writeln!(f, "{:indent$}NIST CURVE: {}", "", nist, indent = indent + 2)?;
+                    }
+                }
+                (Some(curve), None) => {
+                    writeln!(f, "{:indent$}ASN1 OID: {}", "", curve, indent = indent + 2)?;
+                }
+                (None, _) => {
+                    writeln!(
+                        f,
+                        "{:indent$}Curve: <explicit or missing parameters>",
+                        "",
+                        indent = indent + 2
+                    )?;
+                }
+            }
+        }
+        Ok(PublicKey::DSA(y)) => {
+            writeln!(
+                f,
+                "{:indent$}Public-Key: ({} bit)",
+                "",
+                bit_length(y),
+                indent = indent + 2,
+            )?;
+            writeln!(f, "{:indent$}pub:", "", indent = indent + 2)?;
+            print_hex_rows(f, y, indent + 4)?;
+            // Dss-Parms ::= SEQUENCE { p INTEGER, q INTEGER, g INTEGER }
+            if let Some(params) = &public_key.algorithm.parameters {
+                let mut i = params.data;
+                for name in ["P", "Q", "G"] {
+                    match parse_der_integer(i) {
+                        Ok((rem, obj)) => {
+                            i = rem;
+                            if let Ok(value) = obj.as_slice() {
+                                writeln!(f, "{:indent$}{}:", "", name, indent = indent + 2)?;
+                                print_hex_rows(f, value, indent + 4)?;
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                }
+            }
+        }
+        Ok(PublicKey::GostR3410(y)) | Ok(PublicKey::GostR3410_2012(y)) => {
+            writeln!(f, "{:indent$}pub:", "", indent = indent + 2)?;
+            print_hex_rows(f, y, indent + 4)?;
+        }
+        Ok(PublicKey::Unknown(b)) => {
+            writeln!(
+                f,
+                "{:indent$}Unknown key algorithm ({} bytes)",
+                "",
+                b.len(),
+                indent = indent + 2
+            )?;
+            writeln!(
+                f,
+                "{:indent$}{}",
+                "",
+                format_hex_dump(b, 256),
+                indent = indent + 4
+            )?;
+        }
+        Err(_) => {
+            writeln!(f, "{:indent$}Invalid Public Key", "", indent = indent + 2)?;
+        }
+    }
 
     Ok(())
 }
@@ -101,6 +341,21 @@ pub fn print_x509_extension(
     extension: &X509Extension,
     indent: usize,
 ) -> fmt::Result {
+    if extension.oid == OID_CT_LIST_SCT {
+        writeln!(f, "{:indent$}CT Precertificate SCTs:", "", indent = indent)?;
+        match SignedCertificateTimestampList::from_extension_value(extension.value) {
+            Ok(list) => {
+                for sct in list.scts() {
+                    write!(f, "{}", sct.display(None).indent(indent + 2))?;
+                }
+            }
+            Err(e) => {
+                writeln!(f, "{:indent$}<invalid: {}>", "", e, indent = indent + 2)?;
+            }
+        }
+        return Ok(());
+    }
+
     match extension.parsed_extension() {
         ParsedExtension::AuthorityKeyIdentifier(aki) => {
             writeln!(
@@ -150,37 +405,6 @@ pub fn print_x509_extension(
                 indent2 = indent + 2
             )?;
         }
-        ParsedExtension::CRLDistributionPoints(points) => {
-            writeln!(
-                f,
-                "{:indent$}X509v3 CRL Distribution Points:",
-                "",
-                indent = indent
-            )?;
-
-            for point in points.iter() {
-                if let Some(name) = &point.distribution_point {
-                    writeln!(
-                        f,
-                        "{:indent$}Full Name: {:?}",
-                        "",
-                        name,
-                        indent = indent + 2
-                    )?;
-                }
-                if let Some(reasons) = &point.reasons {
-                    writeln!(f, "{:indent$}Reasons: {}", "", reasons, indent = indent + 2)?;
-                }
-                if let Some(crl_issuer) = &point.crl_issuer {
-                    write!(f, "{:indent$}CRL Issuer: ", "", indent = indent + 2)?;
-                    for gn in crl_issuer {
-                        write!(f, "{} ", gn)?;
-                    }
-                    writeln!(f)?;
-                }
-                writeln!(f)?;
-            }
-        }
         ParsedExtension::KeyUsage(ku) => {
             writeln!(
                 f,
@@ -210,20 +434,7 @@ pub fn print_x509_extension(
                         format!("DNS:{}", s)
                     }
                     GeneralName::IPAddress(b) => {
-                        let ip = match b.len() {
-                            4 => {
-                                let b = <[u8; 4]>::try_from(*b).unwrap();
-                                let ip = Ipv4Addr::from(b);
-                                format!("{}", ip)
-                            }
-                            16 => {
-                                let b = <[u8; 16]>::try_from(*b).unwrap();
-                                let ip = Ipv6Addr::from(b);
-                                format!("{}", ip)
-                            }
-                            l => format!("invalid (len={})", l),
-                        };
-                        format!("IP Address:{}", ip)
+                        format!("IP Address:{}", format_ip_address(b))
                     }
                     _ => {
                         format!("{:?}", name)
@@ -252,10 +463,14 @@ pub fn print_x509_extension(
             )?;
         }
         x => {
+            if let Some(decoded) = DecodedExtension::decode(extension) {
+                return write!(f, "{}", decoded.display().indent(indent));
+            }
             writeln!(
                 f,
-                "{:indent$}X509v3 Unknown Extension:\n{:indent2$}{:?}",
+                "{:indent$}{}:\n{:indent2$}{:?}",
                 "",
+                format_oid(&extension.oid, oid::registry()),
                 "",
                 x,
                 indent = indent,
@@ -265,3 +480,51 @@ pub fn print_x509_extension(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use x509_parser::prelude::FromDer;
+
+    use super::*;
+
+    struct Ski<'a>(SubjectPublicKeyInfo<'a>);
+
+    impl fmt::Display for Ski<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            print_x509_ski(f, &self.0, 0)
+        }
+    }
+
+    #[test]
+    fn public_key_sizes() {
+        let pem = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/public_keys.pem"
+        ))
+        .unwrap();
+        let headers: Vec<_> = Pem::iter_from_buffer(&pem)
+            .map(|pem| {
+                let pem = pem.unwrap();
+                let (_, spki) = SubjectPublicKeyInfo::from_der(&pem.contents).unwrap();
+                let text = Ski(spki).to_string();
+                let mut lines = text.lines().skip(1);
+                let header = lines.next().unwrap().trim().to_owned();
+                let curve = lines.find(|line| line.contains("ASN1 OID"));
+                (header, curve.map(|line| line.trim().to_owned()))
+            })
+            .collect();
+        let expected = [
+            ("Public-Key: (2048 bit)", None),
+            ("Public-Key: (256 bit)", Some("ASN1 OID: prime256v1")),
+            ("Public-Key: (384 bit)", Some("ASN1 OID: secp384r1")),
+            ("Public-Key: (521 bit)", Some("ASN1 OID: secp521r1")),
+            ("Public-Key: (256 bit)", Some("ASN1 OID: secp256k1")),
+            ("ED25519 Public-Key:", None),
+        ];
+        assert_eq!(headers.len(), expected.len());
+        for ((header, curve), (expected_header, expected_curve)) in headers.iter().zip(expected) {
+            assert_eq!(header, expected_header);
+            assert_eq!(curve.as_deref(), expected_curve);
+        }
+    }
+}
@@ -0,0 +1,411 @@
+#!/usr/bin/env python3
+"""Generate the certificates, SCTs and log responses used by the unit tests.
+
+Requires the `cryptography` package (43 or later). Run from this directory; keys and ECDSA
+signatures are deterministic, so every run produces the same files.
+"""
+
+import base64
+import datetime
+import hashlib
+import json
+import struct
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec, ed25519, rsa
+from cryptography.x509.oid import NameOID
+
+NOW = datetime.datetime(2022, 11, 1, tzinfo=datetime.timezone.utc)
+TIMESTAMP = 1667260800000
+
+
+def spki(key):
+    return key.public_bytes(
+        serialization.Encoding.DER, serialization.PublicFormat.SubjectPublicKeyInfo
+    )
+
+
+def u24(n):
+    return struct.pack(">I", n)[1:]
+
+
+def tlv(tag, content):
+    n = len(content)
+    if n < 0x80:
+        length = bytes([n])
+    elif n < 0x100:
+        length = bytes([0x81, n])
+    else:
+        length = bytes([0x82, n >> 8, n & 0xFF])
+    return bytes([tag]) + length + content
+
+
+def oid(*arcs):
+    return tlv(0x06, bytes(arcs))
+
+
+def write_pem(name, certificate):
+    with open(name, "wb") as f:
+        f.write(certificate.public_bytes(serialization.Encoding.PEM))
+
+
+def sign(builder, key):
+    return builder.sign(key, hashes.SHA256(), ecdsa_deterministic=True)
+
+
+def ecdsa():
+    return ec.ECDSA(hashes.SHA256(), deterministic_signing=True)
+
+
+def name(cn):
+    return x509.Name([x509.NameAttribute(NameOID.COMMON_NAME, cn)])
+
+
+ca_key = ec.derive_private_key(0xCA, ec.SECP256R1())
+ca = sign(
+    x509.CertificateBuilder()
+    .subject_name(name("ctlog Test CA"))
+    .issuer_name(name("ctlog Test CA"))
+    .public_key(ca_key.public_key())
+    .serial_number(1)
+    .not_valid_before(NOW)
+    .not_valid_after(NOW + datetime.timedelta(days=3650))
+    .add_extension(x509.BasicConstraints(ca=True, path_length=None), True),
+    ca_key,
+)
+write_pem("ca.pem", ca)
+
+leaf_key = ec.derive_private_key(0x1EAF, ec.SECP256R1())
+
+
+def leaf(
+    lifetime=datetime.timedelta(days=90),
+    serial=0x4242,
+    issuer=ca.subject,
+    subject=name("www.example.com"),
+):
+    return (
+        x509.CertificateBuilder()
+        .subject_name(subject)
+        .issuer_name(issuer)
+        .public_key(leaf_key.public_key())
+        .serial_number(serial)
+        .not_valid_before(NOW)
+        .not_valid_after(NOW + lifetime)
+        .add_extension(x509.SubjectAlternativeName([x509.DNSName("www.example.com")]), False)
+    )
+
+
+# The logged TBSCertificate, the precertificate and the final certificate
+tbs = sign(leaf(), ca_key).tbs_certificate_bytes
+with open("tbs.der", "wb") as f:
+    f.write(tbs)
+write_pem("precert.pem", sign(leaf().add_extension(x509.PrecertPoison(), True), ca_key))
+
+log_key = ec.derive_private_key(0x109, ec.SECP256R1())
+log_id = hashlib.sha256(spki(log_key.public_key())).digest()
+with open("log_key.der", "wb") as f:
+    f.write(spki(log_key.public_key()))
+
+issuer_key_hash = hashlib.sha256(spki(ca_key.public_key())).digest()
+signed = (
+    struct.pack(">BBQH", 0, 0, TIMESTAMP, 1)
+    + issuer_key_hash
+    + u24(len(tbs))
+    + tbs
+    + b"\x00\x00"
+)
+with open("sct_signed_data.bin", "wb") as f:
+    f.write(signed)
+signature = log_key.sign(signed, ecdsa())
+sct = (
+    b"\x00"
+    + log_id
+    + struct.pack(">Q", TIMESTAMP)
+    + b"\x00\x00\x04\x03"
+    + struct.pack(">H", len(signature))
+    + signature
+)
+sct_list = struct.pack(">H", len(sct)) + sct
+sct_list = struct.pack(">H", len(sct_list)) + sct_list
+extension_value = tlv(0x04, sct_list)
+write_pem(
+    "final.pem",
+    sign(
+        leaf().add_extension(
+            x509.UnrecognizedExtension(
+                x509.ObjectIdentifier("1.3.6.1.4.1.11129.2.4.2"), extension_value
+            ),
+            False,
+        ),
+        ca_key,
+    ),
+)
+
+with open("loglist.json", "w") as f:
+    json.dump(
+        {
+            "version": "1.0",
+            "log_list_timestamp": "2022-11-01T00:00:00Z",
+            "operators": [
+                {
+                    "name": "Test Operator",
+                    "email": [],
+                    "logs": [
+                        {
+                            "description": "Test Log",
+                            "log_id": base64.b64encode(log_id).decode(),
+                            "key": base64.b64encode(spki(log_key.public_key())).decode(),
+                            "url": "https://ct.example.com/",
+                            "mmd": 86400,
+                            "state": {"usable": {"timestamp": "2022-01-01T00:00:00Z"}},
+                        }
+                    ],
+                }
+            ],
+        },
+        f,
+        indent=2,
+    )
+
+# An STH for the 8-leaf tree of the RFC 9162 test vectors
+root_hash = bytes.fromhex("5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328")
+sth_timestamp = TIMESTAMP + 3600 * 1000
+tree_head = struct.pack(">BBQQ", 0, 1, sth_timestamp, 8) + root_hash
+signature = log_key.sign(tree_head, ecdsa())
+with open("sth.json", "w") as f:
+    json.dump(
+        {
+            "tree_size": 8,
+            "timestamp": sth_timestamp,
+            "sha256_root_hash": base64.b64encode(root_hash).decode(),
+            "tree_head_signature": base64.b64encode(
+                b"\x04\x03" + struct.pack(">H", len(signature)) + signature
+            ).decode(),
+        },
+        f,
+        indent=2,
+    )
+
+# Successful OCSP responses with the SCT list in their single response extension
+cert_id = tlv(
+    0x30,
+    tlv(0x30, oid(0x2B, 14, 3, 2, 26) + b"\x05\x00")
+    + tlv(0x04, b"\x00" * 20)
+    + tlv(0x04, b"\x00" * 20)
+    + tlv(0x02, b"\x42\x42"),
+)
+
+
+def write_ocsp(filename, cert_status, next_update=b""):
+    single = tlv(
+        0x30,
+        cert_id
+        + cert_status
+        + tlv(0x18, b"20221101000000Z")
+        + next_update
+        + tlv(
+            0xA1,
+            tlv(0x30, tlv(0x30, oid(0x2B, 6, 1, 4, 1, 0xD6, 0x79, 2, 4, 5) + tlv(0x04, extension_value))),
+        ),
+    )
+    response_data = tlv(
+        0x30, tlv(0xA2, tlv(0x04, b"\x01" * 20)) + tlv(0x18, b"20221101000000Z") + tlv(0x30, single)
+    )
+    basic = tlv(
+        0x30,
+        response_data + tlv(0x30, oid(0x2A, 0x86, 0x48, 0xCE, 0x3D, 4, 3, 2)) + tlv(0x03, b"\x00\x01\x02"),
+    )
+    with open(filename, "wb") as f:
+        f.write(
+            tlv(
+                0x30,
+                tlv(0x0A, b"\x00") + tlv(0xA0, tlv(0x30, oid(0x2B, 6, 1, 5, 5, 7, 0x30, 1, 1) + tlv(0x04, basic))),
+            )
+        )
+
+
+# certStatus good [0] IMPLICIT NULL
+write_ocsp("ocsp.der", b"\x80\x00")
+# certStatus revoked [1] IMPLICIT RevokedInfo (keyCompromise), with a nextUpdate [0]
+write_ocsp(
+    "ocsp_revoked.der",
+    tlv(0xA1, tlv(0x18, b"20221015000000Z") + tlv(0xA0, tlv(0x0A, b"\x01"))),
+    tlv(0xA0, tlv(0x18, b"20221108000000Z")),
+)
+
+# Certificates on both sides of the 180-day lifetime boundary of the CT policies
+write_pem("lifetime_180d.pem", sign(leaf(datetime.timedelta(days=180)), ca_key))
+write_pem(
+    "lifetime_180d_1h.pem",
+    sign(leaf(datetime.timedelta(days=180, hours=1)), ca_key),
+)
+
+# Chains for the validator: an intermediate CA with pathLen 0, a CA below it exceeding the
+# constraint, and its leaf
+intermediate_key = ec.derive_private_key(0x17E, ec.SECP256R1())
+intermediate = sign(
+    x509.CertificateBuilder()
+    .subject_name(name("ctlog Test Intermediate"))
+    .issuer_name(ca.subject)
+    .public_key(intermediate_key.public_key())
+    .serial_number(2)
+    .not_valid_before(NOW)
+    .not_valid_after(NOW + datetime.timedelta(days=1825))
+    .add_extension(x509.BasicConstraints(ca=True, path_length=0), True),
+    ca_key,
+)
+write_pem("intermediate.pem", intermediate)
+sub_intermediate_key = ec.derive_private_key(0x5B, ec.SECP256R1())
+sub_intermediate = sign(
+    x509.CertificateBuilder()
+    .subject_name(name("ctlog Test Sub-Intermediate"))
+    .issuer_name(intermediate.subject)
+    .public_key(sub_intermediate_key.public_key())
+    .serial_number(3)
+    .not_valid_before(NOW)
+    .not_valid_after(NOW + datetime.timedelta(days=1825))
+    .add_extension(x509.BasicConstraints(ca=True, path_length=None), True),
+    intermediate_key,
+)
+write_pem("sub_intermediate.pem", sub_intermediate)
+write_pem("sub_leaf.pem", sign(leaf(issuer=sub_intermediate.subject), sub_intermediate_key))
+
+# A certificate issued by the (non-CA) leaf
+write_pem(
+    "leaf_issued.pem",
+    sign(
+        x509.CertificateBuilder()
+        .subject_name(name("not.example.com"))
+        .issuer_name(name("www.example.com"))
+        .public_key(leaf_key.public_key())
+        .serial_number(4)
+        .not_valid_before(NOW)
+        .not_valid_after(NOW + datetime.timedelta(days=90)),
+        leaf_key,
+    ),
+)
+
+# A leaf signed with ecdsa-with-SHA512, which x509-parser cannot verify
+write_pem("sha512.pem", leaf().sign(ca_key, hashes.SHA512(), ecdsa_deterministic=True))
+
+# A version 1 root, without extensions, and a leaf it issued
+v1_key = ec.derive_private_key(0x71, ec.SECP256R1())
+v1_name = name("ctlog Test V1 Root")
+ecdsa_with_sha256 = tlv(0x30, oid(0x2A, 0x86, 0x48, 0xCE, 0x3D, 4, 3, 2))
+v1_tbs = tlv(
+    0x30,
+    tlv(0x02, b"\x01")
+    + ecdsa_with_sha256
+    + v1_name.public_bytes()
+    + tlv(0x30, tlv(0x17, b"221101000000Z") + tlv(0x17, b"321029000000Z"))
+    + v1_name.public_bytes()
+    + spki(v1_key.public_key()),
+)
+v1_root = tlv(
+    0x30, v1_tbs + ecdsa_with_sha256 + tlv(0x03, b"\x00" + v1_key.sign(v1_tbs, ecdsa()))
+)
+write_pem("v1_root.pem", x509.load_der_x509_certificate(v1_root))
+write_pem("v1_leaf.pem", sign(leaf(issuer=v1_name), v1_key))
+
+# A Precertificate Signing Certificate of the CA and a precertificate it issued, and a
+# precertificate with a non-critical poison extension
+psc_key = ec.derive_private_key(0x95C, ec.SECP256R1())
+psc = sign(
+    x509.CertificateBuilder()
+    .subject_name(name("ctlog Test Precertificate Signing"))
+    .issuer_name(ca.subject)
+    .public_key(psc_key.public_key())
+    .serial_number(5)
+    .not_valid_before(NOW)
+    .not_valid_after(NOW + datetime.timedelta(days=1825))
+    .add_extension(x509.BasicConstraints(ca=True, path_length=0), True)
+    .add_extension(
+        x509.ExtendedKeyUsage([x509.ObjectIdentifier("1.3.6.1.4.1.11129.2.4.4")]), False
+    )
+    .add_extension(x509.AuthorityKeyIdentifier.from_issuer_public_key(ca_key.public_key()), False),
+    ca_key,
+)
+write_pem("psc.pem", psc)
+write_pem(
+    "psc_precert.pem",
+    sign(
+        leaf(issuer=psc.subject)
+        .add_extension(x509.AuthorityKeyIdentifier.from_issuer_public_key(psc_key.public_key()), False)
+        .add_extension(x509.PrecertPoison(), True),
+        psc_key,
+    ),
+)
+write_pem(
+    "precert_noncritical.pem",
+    sign(
+        leaf().add_extension(
+            x509.UnrecognizedExtension(x509.ObjectIdentifier("1.3.6.1.4.1.11129.2.4.3"), b"\x05\x00"),
+            False,
+        ),
+        ca_key,
+    ),
+)
+
+# The final certificate with the length of its serial number in a non-minimal 5-byte form,
+# which x509-parser accepts but DER forbids
+final = x509.load_pem_x509_certificate(open("final.pem", "rb").read())
+der = final.public_bytes(serialization.Encoding.DER)
+der = der.replace(tlv(0x02, b"\x42\x42"), b"\x02\x85\x00\x00\x00\x00\x02\x42\x42", 1)
+# Grow the 2-byte lengths of the Certificate and its TBSCertificate by the 5 added bytes
+for offset in (0, 4):
+    (length,) = struct.unpack(">H", der[offset + 2 : offset + 4])
+    der = der[: offset + 2] + struct.pack(">H", length + 5) + der[offset + 4 :]
+with open("final_long_serial.pem", "w") as f:
+    encoded = base64.b64encode(der).decode()
+    f.write("-----BEGIN CERTIFICATE-----\n")
+    f.writelines(encoded[i : i + 64] + "\n" for i in range(0, len(encoded), 64))
+    f.write("-----END CERTIFICATE-----\n")
+
+
+# Validation levels, in order: EV, DV and OV policies; a CA-specific EV policy; IV and OV
+# policies; the EV subject attributes; an organizationName; a givenName
+def policies(*oids):
+    return x509.CertificatePolicies(
+        [x509.PolicyInformation(x509.ObjectIdentifier(oid), None) for oid in oids]
+    )
+
+
+def subject(*attributes):
+    return x509.Name(
+        [x509.NameAttribute(oid, value) for oid, value in attributes]
+        + [x509.NameAttribute(NameOID.COMMON_NAME, "www.example.com")]
+    )
+
+
+with open("classification.pem", "wb") as f:
+    for builder in [
+        leaf().add_extension(policies("2.23.140.1.1", "2.23.140.1.2.1", "2.23.140.1.2.2"), False),
+        leaf().add_extension(policies("2.16.840.1.114412.2.1"), False),
+        leaf().add_extension(policies("2.23.140.1.2.2", "2.23.140.1.2.3"), False),
+        leaf(
+            subject=subject(
+                (NameOID.BUSINESS_CATEGORY, "Private Organization"),
+                (NameOID.JURISDICTION_COUNTRY_NAME, "FR"),
+                (NameOID.SERIAL_NUMBER, "123456789"),
+                (NameOID.ORGANIZATION_NAME, "Example"),
+            )
+        ),
+        leaf(subject=subject((NameOID.ORGANIZATION_NAME, "Example"))),
+        leaf(subject=subject((NameOID.GIVEN_NAME, "Jane"), (NameOID.SURNAME, "Doe"))),
+    ]:
+        f.write(sign(builder, ca_key).public_bytes(serialization.Encoding.PEM))
+
+# Public keys of each type, in order: RSA 2048, P-256, P-384, P-521, secp256k1 and Ed25519
+rsa_modulus = (1 << 2047) | int.from_bytes(hashlib.sha512(b"ctlog").digest(), "big") | 1
+with open("public_keys.pem", "wb") as f:
+    for key in [
+        rsa.RSAPublicNumbers(65537, rsa_modulus).public_key(),
+        leaf_key.public_key(),
+        ec.derive_private_key(0x384, ec.SECP384R1()).public_key(),
+        ec.derive_private_key(0x521, ec.SECP521R1()).public_key(),
+        ec.derive_private_key(0x256, ec.SECP256K1()).public_key(),
+        ed25519.Ed25519PrivateKey.from_private_bytes(bytes(range(32))).public_key(),
+    ]:
+        f.write(key.public_bytes(serialization.Encoding.PEM, serialization.PublicFormat.SubjectPublicKeyInfo))